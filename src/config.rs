@@ -0,0 +1,273 @@
+use crate::prelude::*;
+use crate::email::{EmailTemplates, MailerConfig};
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::{Build, Rocket};
+use serde::{Deserializer, de::Error as _};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "mongodb")]
+use mongodb::bson::oid::ObjectId;
+#[cfg(feature = "redis")]
+use crate::session::redis::RedisOptions;
+
+/// The `[auth]` section of the Rocket configuration, read by [`AuthFairing`].
+/// Durations are given in seconds.
+/// ```toml
+/// [default.auth]
+/// breached_passwords = "pwned-passwords-sha1-ordered-by-hash.txt"
+///
+/// [default.auth.database]
+/// backend = "mongodb"
+/// url = "mongodb://localhost:27017"
+/// name = "rocket_auth_nosql"
+///
+/// [default.auth.sessions]
+/// backend = "redis"
+/// url = "redis://127.0.0.1/"
+/// pool_size = 8
+///
+/// [default.auth.policy]
+/// idle_timeout = 900
+///
+/// [default.auth.verification]
+/// token_ttl = 7200
+///
+/// [default.auth.password]
+/// min_length = 12
+/// banned_words = ["example"]
+/// min_score = 3
+/// history = 10
+///
+/// [default.auth.cookie]
+/// name = "app_session"
+/// same_site = "lax"
+///
+/// [default.auth.mail]
+/// host = "smtp.example.com"
+/// tls = "starttls"
+/// from = "Example <accounts@example.com>"
+/// language = "en"
+/// verification_url = "https://example.com/verify/"
+/// credentials = { username = "accounts", password = "secret" }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthConfig {
+    /// Where users are stored.
+    pub database: DatabaseConfig,
+    /// Where sessions are stored. It defaults to memory.
+    #[serde(default)]
+    pub sessions: SessionsConfig,
+    /// The limits on the length of sessions.
+    #[serde(default)]
+    pub policy: SessionPolicy,
+    /// The lifetime of account verification tokens.
+    #[serde(default)]
+    pub verification: VerificationPolicy,
+    /// The rules new passwords must follow.
+    #[serde(default)]
+    pub password: PasswordPolicy,
+    /// The path of a list of breached passwords, which new passwords cannot be in.
+    /// It is opened with [`BreachedPasswords::open`] when the backends are.
+    #[serde(default)]
+    pub breached_passwords: Option<PathBuf>,
+    /// The attributes of the session cookie.
+    #[serde(default)]
+    pub cookie: CookieConfig,
+    /// The SMTP relay used to send emails. No email is sent without it.
+    #[serde(default)]
+    pub mail: Option<MailerConfig>,
+}
+
+/// The database backend used to store users, selected by its `backend` key.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum DatabaseConfig {
+    /// An in-memory database, see [`Users::open_memory`].
+    Memory,
+    /// A MongoDB database, see [`Users::open_mongodb`].
+    #[cfg(feature = "mongodb")]
+    Mongodb { url: String, name: String },
+    /// A SQLite database, see [`Users::open_sqlite`].
+    #[cfg(feature = "sqlite")]
+    Sqlite { url: String },
+    /// A PostgreSQL database, see [`Users::open_postgres`].
+    #[cfg(feature = "postgres")]
+    Postgres { url: String },
+}
+
+/// The backend used to store sessions, selected by its `backend` key.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum SessionsConfig {
+    /// Sessions are kept in memory and lost on restart.
+    #[default]
+    Memory,
+    /// Sessions are stored on a redis server, see [`Users::open_redis_with`].
+    #[cfg(feature = "redis")]
+    Redis(RedisOptions),
+}
+
+impl AuthConfig {
+    /// Opens the configured database and session store.
+    pub async fn open<Id: ConfigUserId>(&self) -> Result<Users<Id>> {
+        let mut users = Id::open_database(&self.database).await?;
+        match &self.sessions {
+            SessionsConfig::Memory => {}
+            #[cfg(feature = "redis")]
+            SessionsConfig::Redis(options) => users.open_redis_with(options.clone())?,
+        }
+        users.set_session_policy(self.policy);
+        users.set_verification_policy(self.verification);
+        let mut password = self.password.clone();
+        if let Some(path) = &self.breached_passwords {
+            password.breached_passwords = Some(Arc::new(BreachedPasswords::open(path)?));
+        }
+        users.set_password_policy(password);
+        users.set_cookie_config(self.cookie.clone());
+        match &self.mail {
+            Some(mail) => users.with_mailer(mail.clone()),
+            None => {
+                log::warn!("No `mail` configuration: accounts are verified on signup, and passwords can't be reset by email.");
+                Ok(users)
+            }
+        }
+    }
+}
+
+/// The user id types of the backends that [`AuthConfig`] can open.
+/// MongoDB uses `ObjectId`, while the SQL and in-memory backends use `i64`.
+#[rocket::async_trait]
+pub trait ConfigUserId: UserId {
+    /// Opens the database described by `config`, failing if it doesn't use this id type.
+    async fn open_database(config: &DatabaseConfig) -> Result<Users<Self>>;
+}
+
+#[cfg(feature = "mongodb")]
+#[rocket::async_trait]
+impl ConfigUserId for ObjectId {
+    async fn open_database(config: &DatabaseConfig) -> Result<Users<Self>> {
+        match config {
+            DatabaseConfig::Mongodb { url, name } => Users::open_mongodb(url, name).await,
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::ConfigurationError(
+                "only the mongodb backend stores users with `ObjectId` ids".into(),
+            )),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl ConfigUserId for i64 {
+    async fn open_database(config: &DatabaseConfig) -> Result<Users<Self>> {
+        match config {
+            DatabaseConfig::Memory => Ok(Users::open_memory()),
+            #[cfg(feature = "sqlite")]
+            DatabaseConfig::Sqlite { url } => Users::open_sqlite(url).await,
+            #[cfg(feature = "postgres")]
+            DatabaseConfig::Postgres { url } => Users::open_postgres(url).await,
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::ConfigurationError(
+                "the mongodb backend stores users with `ObjectId` ids, not `i64`".into(),
+            )),
+        }
+    }
+}
+
+/// A fairing that builds [`Users`] from the `[auth]` section of the Rocket configuration,
+/// and adds it to the managed state. Ignition fails if the section is missing or invalid,
+/// or if the database cannot be opened. See [`AuthConfig`] for the available settings.
+/// Templates for the emails can be given with [`AuthFairing::with_email_templates`].
+/// ```rust,no_run
+/// # use rocket::{get, routes};
+/// # use rocket_auth_nosql::{AuthFairing, DefaultUserId, User};
+/// #[get("/")]
+/// fn index(user: User) -> String {
+///     format!("Hello {}.", user.email())
+/// }
+///
+/// #[rocket::launch]
+/// fn rocket() -> _ {
+///     rocket::build()
+///         .mount("/", routes![index])
+///         .attach(AuthFairing::<DefaultUserId>::new())
+/// }
+/// ```
+pub struct AuthFairing<Id = DefaultUserId> {
+    templates: Mutex<Option<Box<dyn EmailTemplates>>>,
+    _id: PhantomData<fn() -> Id>,
+}
+
+impl<Id: ConfigUserId> AuthFairing<Id> {
+    pub fn new() -> Self {
+        AuthFairing {
+            templates: Mutex::new(None),
+            _id: PhantomData,
+        }
+    }
+
+    /// Writes emails with `templates`, see [`Users::with_email_templates`].
+    /// Ignition fails if the configuration has no `mail` section.
+    pub fn with_email_templates(self, templates: impl EmailTemplates + 'static) -> Self {
+        *self.templates.lock().unwrap() = Some(Box::new(templates));
+        self
+    }
+}
+
+impl<Id: ConfigUserId> Default for AuthFairing<Id> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[rocket::async_trait]
+impl<Id: ConfigUserId> Fairing for AuthFairing<Id> {
+    fn info(&self) -> Info {
+        Info {
+            name: "rocket_auth_nosql",
+            kind: Kind::Ignite,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        let config: AuthConfig = match rocket.figment().extract_inner("auth") {
+            Ok(config) => config,
+            Err(err) => {
+                log::error!("Invalid `auth` configuration: {}", err);
+                return Err(rocket);
+            }
+        };
+        let templates = self.templates.lock().unwrap().take();
+        let users = config.open::<Id>().await.and_then(|mut users| {
+            if let Some(templates) = templates {
+                users.set_email_templates(templates)?;
+            }
+            Ok(users)
+        });
+        match users {
+            Ok(users) => Ok(rocket.manage(users)),
+            Err(err) => {
+                log::error!("Failed to open the `auth` backends: {}", err);
+                Err(rocket)
+            }
+        }
+    }
+}
+
+/// Reads a non-negative number of seconds as a [`Duration`].
+pub(crate) fn deserialize_secs<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let secs = f64::deserialize(deserializer)?;
+    if secs.is_finite() && secs >= 0.0 && secs <= u64::MAX as f64 {
+        Ok(Duration::from_secs_f64(secs))
+    } else {
+        Err(D::Error::custom(format!("invalid duration of {} seconds", secs)))
+    }
+}
+
+/// Reads an optional non-negative number of seconds as a [`Duration`].
+pub(crate) fn deserialize_optional_secs<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    #[derive(Deserialize)]
+    struct Secs(#[serde(deserialize_with = "deserialize_secs")] Duration);
+    let secs = Option::<Secs>::deserialize(deserializer)?;
+    Ok(secs.map(|Secs(duration)| duration))
+}
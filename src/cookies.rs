@@ -1,8 +1,91 @@
 use crate::prelude::*;
-use rocket::http::{CookieJar, Status};
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
 use rocket::request::{FromRequest, Outcome, Request};
+use serde::{Deserializer, de::Error as _};
 use serde_json::from_str;
-use mongodb::bson::{oid::ObjectId};
+
+/// The attributes of the session cookie, set with [`Users::set_cookie_config`].
+/// Its max-age always matches the time left in the session.
+/// ```rust
+/// # use rocket_auth_nosql::{CookieConfig, Users};
+/// let mut users = Users::open_memory();
+/// users.set_cookie_config(CookieConfig {
+///     name: "admin_session".into(),
+///     domain: Some("admin.example.com".into()),
+///     secure: true,
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CookieConfig {
+    /// The name of the cookie. It defaults to `rocket_auth_nosql`.
+    pub name: String,
+    /// The path the cookie is sent to. It defaults to `/`.
+    pub path: String,
+    /// The domain the cookie is sent to. It defaults to `None`, meaning only the host that set it.
+    pub domain: Option<String>,
+    /// The `SameSite` attribute of the cookie, read as `"strict"`, `"lax"` or `"none"`.
+    /// It defaults to [`SameSite::Strict`].
+    #[serde(deserialize_with = "deserialize_same_site")]
+    pub same_site: SameSite,
+    /// Whether the cookie is only sent over HTTPS. It defaults to `false`.
+    pub secure: bool,
+    /// Whether the cookie is hidden from scripts. It defaults to `true`.
+    pub http_only: bool,
+}
+
+impl Default for CookieConfig {
+    fn default() -> Self {
+        CookieConfig {
+            name: "rocket_auth_nosql".into(),
+            path: "/".into(),
+            domain: None,
+            same_site: SameSite::Strict,
+            secure: false,
+            http_only: true,
+        }
+    }
+}
+
+impl CookieConfig {
+    /// Builds a cookie holding `value`, which expires once `max_age` has elapsed.
+    pub(crate) fn build(&self, value: String, max_age: Duration) -> Cookie<'static> {
+        let max_age = rocket::time::Duration::seconds(max_age.as_secs() as i64);
+        let mut cookie = Cookie::build(self.name.clone(), value)
+            .path(self.path.clone())
+            .same_site(self.same_site)
+            .secure(self.secure)
+            .http_only(self.http_only)
+            .max_age(max_age)
+            .expires(rocket::time::OffsetDateTime::now_utc() + max_age)
+            .finish();
+        if let Some(domain) = &self.domain {
+            cookie.set_domain(domain.clone());
+        }
+        cookie
+    }
+
+    /// A cookie that matches the session cookie, used to remove it.
+    pub(crate) fn removal(&self) -> Cookie<'static> {
+        let mut cookie = Cookie::named(self.name.clone());
+        cookie.set_path(self.path.clone());
+        if let Some(domain) = &self.domain {
+            cookie.set_domain(domain.clone());
+        }
+        cookie
+    }
+}
+
+fn deserialize_same_site<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SameSite, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    match value.to_lowercase().as_str() {
+        "strict" => Ok(SameSite::Strict),
+        "lax" => Ok(SameSite::Lax),
+        "none" => Ok(SameSite::None),
+        _ => Err(D::Error::custom(format!("invalid SameSite value \"{}\", expected strict, lax or none", value))),
+    }
+}
 
 /// The Session guard can be used to retrieve user session data.
 /// Unlike `User`, using session does not verify that the session data is
@@ -14,11 +97,15 @@ use mongodb::bson::{oid::ObjectId};
 /// session data is already captured by the [`Auth`](`crate::Auth`) guard and stored in the public [`session`](`crate::Auth`) field.
 /// So it is not necessary to use them together.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct Session {
+pub struct Session<Id = DefaultUserId> {
     /// It represents the Unix time in which the user logged in. It is measured in seconds.
     pub time_stamp: i64,
+    /// The Unix time, in seconds, at which the session ends regardless of its activity.
+    pub expires: i64,
     /// The user id as it is stored on the database.
-    pub id: ObjectId,
+    pub id: Id,
+    /// A random identifier that tells this session apart from the other sessions of the same user.
+    pub session_id: String,
     /// The user email.
     pub email: String,
     /// An random authentication token key.
@@ -27,12 +114,16 @@ pub struct Session {
 
 
 #[async_trait]
-impl<'r> FromRequest<'r> for Session {
+impl<'r, Id: UserId> FromRequest<'r> for Session<Id> {
     type Error = Error;
-    async fn from_request(request: &'r Request<'_>) -> Outcome<Session, Self::Error> {
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Session<Id>, Self::Error> {
         let cookies = request.cookies();
+        let name = match request.rocket().state::<Users<Id>>() {
+            Some(users) => users.cookie.name.as_str(),
+            None => "rocket_auth_nosql",
+        };
 
-        if let Some(session) = get_session(cookies) {
+        if let Some(session) = get_session(cookies, name) {
             Outcome::Success(session)
         } else {
             Outcome::Failure((Status::Unauthorized, Error::UnauthorizedError))
@@ -40,8 +131,8 @@ impl<'r> FromRequest<'r> for Session {
     }
 }
 
-fn get_session(cookies: &CookieJar) -> Option<Session> {
-    if let Some(session) = cookies.get_private("rocket_auth_nosql") {
+fn get_session<Id: UserId>(cookies: &CookieJar, name: &str) -> Option<Session<Id>> {
+    if let Some(session) = cookies.get_private(name) {
         return from_str(session.value()).ok();
     }
     None
@@ -0,0 +1,109 @@
+use crate::prelude::{Result, *};
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::RwLock;
+use crate::Error::{EmailAlreadyExists, UserNotFoundError};
+
+/// A `DBConnection` that keeps every user in memory.
+/// Nothing is persisted, so it is meant for tests and prototypes.
+/// Users are identified by sequential integer ids.
+#[derive(Default)]
+pub struct MemoryDB {
+    users: RwLock<HashMap<i64, User<i64>>>,
+    last_id: AtomicI64,
+}
+
+impl MemoryDB {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+#[rocket::async_trait]
+impl DBConnection for MemoryDB {
+    type Id = i64;
+    async fn create_user(&self, email: &str, hash: &str, token: &str, is_admin: bool) -> Result<()> {
+        let mut users = self.users.write().unwrap();
+        if users.values().any(|user| user.email == email) {
+            return Err(EmailAlreadyExists);
+        }
+        let id = self.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let user_rec = User {
+            id: Some(id),
+            email: email.to_string(),
+            is_admin,
+            is_verified: false,
+            verification_token: token.to_string(),
+            verification_issued_at: now(),
+            password: hash.to_string(),
+            password_reset_hash: None,
+            password_reset_issued_at: 0,
+            password_history: Vec::new(),
+            language: None,
+        };
+        users.insert(id, user_rec);
+        Ok(())
+    }
+    async fn update_user(&self, user: &User<i64>) -> Result<()> {
+        let mut users = self.users.write().unwrap();
+        let id = user.id.ok_or(UserNotFoundError)?;
+        if users.values().any(|other| other.email == user.email && other.id != user.id) {
+            return Err(EmailAlreadyExists);
+        }
+        match users.get_mut(&id) {
+            Some(user_rec) => {
+                *user_rec = user.clone();
+                Ok(())
+            }
+            None => Err(UserNotFoundError),
+        }
+    }
+    async fn delete_user_by_id(&self, user_id: i64) -> Result<()> {
+        self.users.write().unwrap().remove(&user_id);
+        Ok(())
+    }
+    async fn delete_user_by_email(&self, email: &str) -> Result<()> {
+        self.users.write().unwrap().retain(|_, user| user.email != email);
+        Ok(())
+    }
+    async fn get_user_by_id(&self, user_id: i64) -> Result<User<i64>> {
+        self.users
+            .read()
+            .unwrap()
+            .get(&user_id)
+            .cloned()
+            .ok_or(UserNotFoundError)
+    }
+    async fn get_user_by_email(&self, email: &str) -> Result<User<i64>> {
+        self.users
+            .read()
+            .unwrap()
+            .values()
+            .find(|user| user.email == email)
+            .cloned()
+            .ok_or(UserNotFoundError)
+    }
+    async fn get_user_by_verification_token(&self, token: &str) -> Result<User<i64>> {
+        self.users
+            .read()
+            .unwrap()
+            .values()
+            // Verified users have an empty token.
+            .find(|user| !token.is_empty() && user.verification_token == token)
+            .cloned()
+            .ok_or(UserNotFoundError)
+    }
+    async fn get_user_by_reset_hash(&self, hash: &str) -> Result<User<i64>> {
+        self.users
+            .read()
+            .unwrap()
+            .values()
+            .find(|user| user.password_reset_hash.as_deref() == Some(hash))
+            .cloned()
+            .ok_or(UserNotFoundError)
+    }
+    async fn get_all_users(&self) -> Result<Vec<User<i64>>> {
+        Ok(self.users.read().unwrap().values().cloned().collect())
+    }
+}
@@ -1,65 +1,98 @@
+mod memory;
+#[cfg(feature = "mongodb")]
 mod mongomodel;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub(crate) use memory::MemoryDB;
 
 use crate::prelude::*;
-use mongodb::bson::{oid::ObjectId};
 
 #[rocket::async_trait]
 pub trait DBConnection: Send + Sync {
+    type Id: UserId;
+    async fn init(&self) -> Result<()> {
+        Ok(())
+    }
     async fn create_user(&self, email: &str, hash: &str, token: &str, is_admin: bool) -> Result<()>;
-    async fn update_user(&self, user: &User) -> Result<()>;
-    async fn delete_user_by_id(&self, user_id: ObjectId) -> Result<()>;
+    async fn update_user(&self, user: &User<Self::Id>) -> Result<()>;
+    async fn delete_user_by_id(&self, user_id: Self::Id) -> Result<()>;
     async fn delete_user_by_email(&self, email: &str) -> Result<()>;
-    async fn get_user_by_id(&self, user_id: ObjectId) -> Result<User>;
-    async fn get_user_by_email(&self, email: &str) -> Result<User>;
-    async fn get_all_users(&self) -> Result<Vec<User>>;
+    async fn get_user_by_id(&self, user_id: Self::Id) -> Result<User<Self::Id>>;
+    async fn get_user_by_email(&self, email: &str) -> Result<User<Self::Id>>;
+    async fn get_user_by_verification_token(&self, token: &str) -> Result<User<Self::Id>>;
+    async fn get_user_by_reset_hash(&self, hash: &str) -> Result<User<Self::Id>>;
+    async fn get_all_users(&self) -> Result<Vec<User<Self::Id>>>;
 }
 
 #[rocket::async_trait]
 impl<T: DBConnection> DBConnection for std::sync::Arc<T> {
+    type Id = T::Id;
+    async fn init(&self) -> Result<()> {
+        T::init(self).await
+    }
     async fn create_user(&self, email: &str, hash: &str, token: &str, is_admin: bool) -> Result<(), Error> {
         T::create_user(self, email, hash, token, is_admin).await
     }
-    async fn update_user(&self, user: &User) -> Result<()> {
+    async fn update_user(&self, user: &User<Self::Id>) -> Result<()> {
         T::update_user(self, user).await
     }
-    async fn delete_user_by_id(&self, user_id: ObjectId) -> Result<()> {
+    async fn delete_user_by_id(&self, user_id: Self::Id) -> Result<()> {
         T::delete_user_by_id(self, user_id).await
     }
     async fn delete_user_by_email(&self, email: &str) -> Result<()> {
         T::delete_user_by_email(self, email).await
     }
-    async fn get_user_by_id(&self, user_id: ObjectId) -> Result<User> {
+    async fn get_user_by_id(&self, user_id: Self::Id) -> Result<User<Self::Id>> {
         T::get_user_by_id(self, user_id).await
     }
-    async fn get_user_by_email(&self, email: &str) -> Result<User> {
+    async fn get_user_by_email(&self, email: &str) -> Result<User<Self::Id>> {
         T::get_user_by_email(self, email).await
     }
-    async fn get_all_users(&self) -> Result<Vec<User>> {
+    async fn get_user_by_verification_token(&self, token: &str) -> Result<User<Self::Id>> {
+        T::get_user_by_verification_token(self, token).await
+    }
+    async fn get_user_by_reset_hash(&self, hash: &str) -> Result<User<Self::Id>> {
+        T::get_user_by_reset_hash(self, hash).await
+    }
+    async fn get_all_users(&self) -> Result<Vec<User<Self::Id>>> {
         T::get_all_users(self).await
     }
 }
 
 #[rocket::async_trait]
 impl<T: DBConnection> DBConnection for tokio::sync::Mutex<T> {
+    type Id = T::Id;
+    async fn init(&self) -> Result<()> {
+        self.lock().await.init().await
+    }
     async fn create_user(&self, email: &str, hash: &str, token: &str, is_admin: bool) -> Result<(), Error> {
         self.lock().await.create_user(email, hash, token, is_admin).await
     }
-    async fn update_user(&self, user: &User) -> Result<()> {
+    async fn update_user(&self, user: &User<Self::Id>) -> Result<()> {
         self.lock().await.update_user(user).await
     }
-    async fn delete_user_by_id(&self, user_id: ObjectId) -> Result<()> {
+    async fn delete_user_by_id(&self, user_id: Self::Id) -> Result<()> {
         self.lock().await.delete_user_by_id(user_id).await
     }
     async fn delete_user_by_email(&self, email: &str) -> Result<()> {
         self.lock().await.delete_user_by_email(email).await
     }
-    async fn get_user_by_id(&self, user_id: ObjectId) -> Result<User> {
+    async fn get_user_by_id(&self, user_id: Self::Id) -> Result<User<Self::Id>> {
         self.lock().await.get_user_by_id(user_id).await
     }
-    async fn get_user_by_email(&self, email: &str) -> Result<User> {
+    async fn get_user_by_email(&self, email: &str) -> Result<User<Self::Id>> {
         self.lock().await.get_user_by_email(email).await
     }
-    async fn get_all_users(&self) -> Result<Vec<User>> {
+    async fn get_user_by_verification_token(&self, token: &str) -> Result<User<Self::Id>> {
+        self.lock().await.get_user_by_verification_token(token).await
+    }
+    async fn get_user_by_reset_hash(&self, hash: &str) -> Result<User<Self::Id>> {
+        self.lock().await.get_user_by_reset_hash(hash).await
+    }
+    async fn get_all_users(&self) -> Result<Vec<User<Self::Id>>> {
         self.lock().await.get_all_users().await
     }
 }
@@ -10,29 +10,38 @@ const COLLECTION: &str = "users";
 
 #[rocket::async_trait]
 impl DBConnection for Database {
+    type Id = ObjectId;
+    /// Indexes the fields users are looked up by, so finding a user by a token doesn't scan the collection.
+    async fn init(&self) -> Result<()> {
+        let indexes = ["email", "verification_token", "password_reset_hash"]
+            .iter()
+            .map(|field| {
+                IndexModel::builder()
+                    .keys(doc! { *field: 1 })
+                    .options(IndexOptions::builder()
+                        .unique(*field == "email")
+                        .name(field.to_string())
+                        .build())
+                    .build()
+            });
+        self.collection::<User>(COLLECTION)
+            .create_indexes(indexes, None).await?;
+        Ok(())
+    }
     async fn create_user(&self, email: &str, hash: &str, token: &str, is_admin: bool) -> Result<()> {
-        let new_index = IndexModel::builder()
-            .keys(doc!{"email": 1})
-            .options(IndexOptions::builder()
-                .unique(true)
-                .name("email".to_string())
-                .build())
-            .build();
 		let user_rec = User {
             id: None,
 			email: email.to_string(),
-			is_admin: is_admin,
+			is_admin,
             is_verified: false,
             verification_token: token.to_string(),
+            verification_issued_at: now(),
 			password: hash.to_string(),
-            prev_password: None,
-            prev_password_1: None,
-            prev_password_2: None
+			password_reset_hash: None,
+			password_reset_issued_at: 0,
+            password_history: Vec::new(),
+            language: None,
 		};
-        // Ensure the collection index exists for unique email values
-        self.collection::<User>(COLLECTION)
-            .create_index(new_index, None).await?;
-
 		self.collection::<User>(COLLECTION)
 			.insert_one(user_rec, None).await?;
         Ok(())
@@ -89,6 +98,34 @@ impl DBConnection for Database {
             Err(UserNotFoundError)
         }
     }
+    async fn get_user_by_verification_token(&self, token: &str) -> Result<User> {
+        // Verified users have an empty token.
+        if token.is_empty() {
+            return Err(UserNotFoundError);
+        }
+        if let Some(user_rec) = self.collection::<User>(COLLECTION)
+        .find_one(doc! {
+            "verification_token": token.to_string()
+        },
+        None,
+        ).await? {
+            Ok(user_rec)
+        } else {
+            Err(UserNotFoundError)
+        }
+    }
+    async fn get_user_by_reset_hash(&self, hash: &str) -> Result<User> {
+        if let Some(user_rec) = self.collection::<User>(COLLECTION)
+        .find_one(doc! {
+            "password_reset_hash": hash.to_string()
+        },
+        None,
+        ).await? {
+            Ok(user_rec)
+        } else {
+            Err(UserNotFoundError)
+        }
+    }
     async fn get_all_users(&self) -> Result<Vec<User>> {
         let cursor = match self.collection::<User>(COLLECTION)
             .find(None,
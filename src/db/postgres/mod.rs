@@ -0,0 +1,131 @@
+mod sql;
+
+use crate::prelude::{Result, *};
+use sql::*;
+
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::Row;
+use crate::Error::{EmailAlreadyExists, UserNotFoundError};
+
+/// The SQLSTATE code postgres reports when a `UNIQUE` constraint fails.
+const UNIQUE_VIOLATION: &str = "23505";
+
+#[rocket::async_trait]
+impl DBConnection for PgPool {
+    type Id = i64;
+    async fn init(&self) -> Result<()> {
+        sqlx::query(CREATE_TABLE).execute(self).await?;
+        Ok(())
+    }
+    async fn create_user(&self, email: &str, hash: &str, token: &str, is_admin: bool) -> Result<()> {
+        sqlx::query(INSERT_USER)
+            .bind(email)
+            .bind(is_admin)
+            .bind(token)
+            .bind(now())
+            .bind(hash)
+            .execute(self)
+            .await
+            .map_err(unique_email)?;
+        Ok(())
+    }
+    async fn update_user(&self, user: &User<i64>) -> Result<()> {
+        sqlx::query(UPDATE_USER)
+            .bind(user.id())
+            .bind(&user.email)
+            .bind(user.is_admin)
+            .bind(user.is_verified)
+            .bind(&user.verification_token)
+            .bind(user.verification_issued_at)
+            .bind(&user.password)
+            .bind(&user.password_reset_hash)
+            .bind(user.password_reset_issued_at)
+            .bind(serde_json::to_string(&user.password_history)?)
+            .bind(user.language.map(Language::code))
+            .execute(self)
+            .await
+            .map_err(unique_email)?;
+        Ok(())
+    }
+    async fn delete_user_by_id(&self, user_id: i64) -> Result<()> {
+        sqlx::query(REMOVE_BY_ID)
+            .bind(user_id)
+            .execute(self)
+            .await?;
+        Ok(())
+    }
+    async fn delete_user_by_email(&self, email: &str) -> Result<()> {
+        sqlx::query(REMOVE_BY_EMAIL)
+            .bind(email)
+            .execute(self)
+            .await?;
+        Ok(())
+    }
+    async fn get_user_by_id(&self, user_id: i64) -> Result<User<i64>> {
+        let row = sqlx::query(SELECT_BY_ID)
+            .bind(user_id)
+            .fetch_optional(self)
+            .await?
+            .ok_or(UserNotFoundError)?;
+        user_from_row(&row)
+    }
+    async fn get_user_by_email(&self, email: &str) -> Result<User<i64>> {
+        let row = sqlx::query(SELECT_BY_EMAIL)
+            .bind(email)
+            .fetch_optional(self)
+            .await?
+            .ok_or(UserNotFoundError)?;
+        user_from_row(&row)
+    }
+    async fn get_user_by_verification_token(&self, token: &str) -> Result<User<i64>> {
+        // Verified users have an empty token.
+        if token.is_empty() {
+            return Err(UserNotFoundError);
+        }
+        let row = sqlx::query(SELECT_BY_VERIFICATION_TOKEN)
+            .bind(token)
+            .fetch_optional(self)
+            .await?
+            .ok_or(UserNotFoundError)?;
+        user_from_row(&row)
+    }
+    async fn get_user_by_reset_hash(&self, hash: &str) -> Result<User<i64>> {
+        let row = sqlx::query(SELECT_BY_RESET_HASH)
+            .bind(hash)
+            .fetch_optional(self)
+            .await?
+            .ok_or(UserNotFoundError)?;
+        user_from_row(&row)
+    }
+    async fn get_all_users(&self) -> Result<Vec<User<i64>>> {
+        let rows = sqlx::query(SELECT_ALL).fetch_all(self).await?;
+        rows.iter().map(user_from_row).collect()
+    }
+}
+
+fn user_from_row(row: &PgRow) -> Result<User<i64>> {
+    Ok(User {
+        id: Some(row.try_get("id")?),
+        email: row.try_get("email")?,
+        is_admin: row.try_get("is_admin")?,
+        is_verified: row.try_get("is_verified")?,
+        verification_token: row.try_get("verification_token")?,
+        verification_issued_at: row.try_get("verification_issued_at")?,
+        password: row.try_get("password")?,
+        password_reset_hash: row.try_get("password_reset_hash")?,
+        password_reset_issued_at: row.try_get("password_reset_issued_at")?,
+        password_history: serde_json::from_str(row.try_get::<&str, _>("password_history")?)?,
+        language: row.try_get::<Option<&str>, _>("language")?.and_then(Language::from_code),
+    })
+}
+
+fn unique_email(error: sqlx::Error) -> Error {
+    match &error {
+        sqlx::Error::Database(db_error)
+            if db_error.code().as_deref() == Some(UNIQUE_VIOLATION) =>
+        {
+            EmailAlreadyExists
+        }
+        _ => error.into(),
+    }
+}
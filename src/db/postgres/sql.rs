@@ -0,0 +1,57 @@
+pub(crate) const CREATE_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS users (
+    id BIGSERIAL PRIMARY KEY,
+    email TEXT NOT NULL UNIQUE,
+    is_admin BOOL NOT NULL,
+    is_verified BOOL NOT NULL,
+    verification_token TEXT NOT NULL,
+    verification_issued_at BIGINT NOT NULL DEFAULT 0,
+    password TEXT NOT NULL,
+    password_reset_hash TEXT,
+    password_reset_issued_at BIGINT NOT NULL DEFAULT 0,
+    password_history TEXT NOT NULL DEFAULT '[]',
+    language TEXT
+);";
+
+pub(crate) const INSERT_USER: &str = "
+INSERT INTO users (email, is_admin, is_verified, verification_token, verification_issued_at, password)
+VALUES ($1, $2, FALSE, $3, $4, $5);";
+
+pub(crate) const UPDATE_USER: &str = "
+UPDATE users SET
+    email = $2,
+    is_admin = $3,
+    is_verified = $4,
+    verification_token = $5,
+    verification_issued_at = $6,
+    password = $7,
+    password_reset_hash = $8,
+    password_reset_issued_at = $9,
+    password_history = $10,
+    language = $11
+WHERE id = $1;";
+
+/// Selects the columns read by `user_from_row`, by name, from the rows matching `$filter`.
+macro_rules! select_users {
+    ($filter:literal) => {
+        concat!(
+            "SELECT id, email, is_admin, is_verified, verification_token, verification_issued_at, password, ",
+            "password_reset_hash, password_reset_issued_at, password_history, language FROM users",
+            $filter
+        )
+    };
+}
+
+pub(crate) const SELECT_BY_ID: &str = select_users!(" WHERE id = $1;");
+
+pub(crate) const SELECT_BY_EMAIL: &str = select_users!(" WHERE email = $1;");
+
+pub(crate) const SELECT_BY_VERIFICATION_TOKEN: &str = select_users!(" WHERE verification_token = $1;");
+
+pub(crate) const SELECT_BY_RESET_HASH: &str = select_users!(" WHERE password_reset_hash = $1;");
+
+pub(crate) const SELECT_ALL: &str = select_users!(";");
+
+pub(crate) const REMOVE_BY_ID: &str = "DELETE FROM users WHERE id = $1;";
+
+pub(crate) const REMOVE_BY_EMAIL: &str = "DELETE FROM users WHERE email = $1;";
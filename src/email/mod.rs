@@ -1,45 +1,245 @@
+mod template;
+mod transport;
+
+pub use template::{EmailContext, EmailKind, EmailTemplates, RenderedEmail};
+pub(crate) use template::EmailText;
+pub use transport::{MailTransport, MemoryTransport, SentEmail};
+
+use crate::language::Language;
 use crate::prelude::*;
+use lettre::message::{Mailbox, MultiPart, SinglePart};
 use lettre::transport::smtp::authentication::Credentials;
-use lettre::{Transport, SmtpTransport, Message};
+use lettre::{FileTransport, Message, SendmailTransport, SmtpTransport};
 use std::format;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// The transport used to deliver emails, selected in [`MailerConfig::transport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransportKind {
+    /// Emails are sent to the configured SMTP server.
+    Smtp,
+    /// Emails are handed to a local sendmail binary.
+    Sendmail,
+    /// Emails are written as `.eml` files to a directory, which is useful during development.
+    File,
+}
+
+/// How the connection to the SMTP server is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsMode {
+    /// Plain text, on port 25 by default. Only suitable for a relay on the local network.
+    None,
+    /// The connection is upgraded with `STARTTLS`, on port 587 by default.
+    StartTls,
+    /// The connection is encrypted from the start, on port 465 by default.
+    Tls,
+}
+
+/// The username and password used to authenticate with the SMTP server.
+/// The password is hidden when the credentials are printed, so it doesn't end up in logs.
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+pub struct SmtpCredentials {
+    pub username: String,
+    pub password: String,
+}
 
-const SMTP_SERVER: &str = "smtp.gmail.com";
-const SMTP_USERNAME: &str = "testuser";
-const SMTP_PASSWORD: &str = "testpass";
-const FROM_ADDRESS: &str = "Test User <testuser@devnull.null>";
-const NEW_ACCOUNT_ACTIVATION_SUBJ: &str = "You have created a new account that requires activation.";
+impl Debug for SmtpCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmtpCredentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// The settings of the mailer used to send emails, passed to [`Users::with_mailer`].
+/// The SMTP settings only apply to the [`TransportKind::Smtp`] transport.
+/// ```rust
+/// # use rocket_auth_nosql::{Error, MailerConfig, SmtpCredentials, TlsMode, Users};
+/// # fn func() -> Result<(), Error> {
+/// let config = MailerConfig {
+///     host: "smtp.example.com".into(),
+///     tls: TlsMode::StartTls,
+///     credentials: Some(SmtpCredentials {
+///         username: "accounts".into(),
+///         password: "secret".into(),
+///     }),
+///     from: "Example <accounts@example.com>".into(),
+///     ..Default::default()
+/// };
+/// let users = Users::open_memory().with_mailer(config)?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct MailerConfig {
+    /// How emails are delivered. It defaults to [`TransportKind::Smtp`].
+    pub transport: TransportKind,
+    /// The host name of the SMTP server. It defaults to `localhost`.
+    pub host: String,
+    /// The port of the SMTP server. It defaults to the usual port of the [`TlsMode`].
+    pub port: Option<u16>,
+    /// How the connection is secured. It defaults to [`TlsMode::Tls`].
+    pub tls: TlsMode,
+    /// The credentials used to log in, if the server requires them.
+    pub credentials: Option<SmtpCredentials>,
+    /// The sender of the emails, such as `"Example <accounts@example.com>"`.
+    pub from: String,
+    /// The language of the emails. It defaults to [`Language::EN`].
+    pub language: Language,
+    /// The address of the page that verifies accounts, such as `https://example.com/verify/`.
+    /// When set, activation emails contain this address followed by the verification token,
+    /// otherwise they only contain the token.
+    pub verification_url: Option<String>,
+    /// The address of the page where users choose a new password, followed by the token
+    /// in password reset emails. Without it, these emails only contain the token.
+    pub password_reset_url: Option<String>,
+    /// How long to wait for the server when connecting and sending commands.
+    /// It defaults to one minute.
+    #[serde(deserialize_with = "crate::config::deserialize_optional_secs")]
+    pub timeout: Option<Duration>,
+    /// The sendmail binary used by [`TransportKind::Sendmail`]. It defaults to `sendmail`.
+    pub sendmail_command: Option<String>,
+    /// The directory emails are written to by [`TransportKind::File`]. It is required by that transport.
+    pub directory: Option<PathBuf>,
+}
+
+impl Default for MailerConfig {
+    fn default() -> Self {
+        MailerConfig {
+            transport: TransportKind::Smtp,
+            host: "localhost".into(),
+            port: None,
+            tls: TlsMode::Tls,
+            credentials: None,
+            from: String::new(),
+            language: Language::EN,
+            verification_url: None,
+            password_reset_url: None,
+            timeout: Some(Duration::from_secs(60)),
+            sendmail_command: None,
+            directory: None,
+        }
+    }
+}
 
 pub struct Mailer {
-    mailer: SmtpTransport
+    transport: Box<dyn MailTransport>,
+    templates: Option<Box<dyn EmailTemplates>>,
+    from: Mailbox,
+    language: Language,
+    verification_url: Option<String>,
+    password_reset_url: Option<String>,
 }
 
 impl Mailer {
-    fn new() -> Self {
-        Default::default()
+    /// Creates a mailer using the transport described by `config`.
+    pub fn new(config: &MailerConfig) -> Result<Self> {
+        match config.transport {
+            TransportKind::Smtp => Self::with_transport(config, Arc::new(smtp_transport(config)?)),
+            TransportKind::Sendmail => {
+                let transport = match &config.sendmail_command {
+                    Some(command) => SendmailTransport::new_with_command(command),
+                    None => SendmailTransport::new(),
+                };
+                Self::with_transport(config, Arc::new(transport))
+            }
+            TransportKind::File => {
+                let directory = config.directory.as_ref().ok_or_else(|| {
+                    Error::ConfigurationError("the file mail transport requires a `directory`".into())
+                })?;
+                Self::with_transport(config, Arc::new(FileTransport::new(directory)))
+            }
+        }
     }
 
-    fn send_activation_email(&self, to: &str, token: &str) -> Result<()> {
+    /// Creates a mailer sending emails through `transport`.
+    /// Only the sender, the language and the urls of `config` are used.
+    pub fn with_transport(config: &MailerConfig, transport: impl MailTransport + 'static) -> Result<Self> {
+        let from = config.from.parse().map_err(|err| {
+            Error::ConfigurationError(format!("invalid mail sender \"{}\": {}", config.from, err))
+        })?;
+        Ok(Mailer {
+            transport: Box::new(transport),
+            templates: None,
+            from,
+            language: config.language,
+            verification_url: config.verification_url.clone(),
+            password_reset_url: config.password_reset_url.clone(),
+        })
+    }
+
+    /// Writes the emails with `templates` instead of the built-in text.
+    pub(crate) fn set_templates(&mut self, templates: Box<dyn EmailTemplates>) {
+        self.templates = Some(templates);
+    }
+
+    /// The emails below are written in `lang`, or in the language of the mailer if it is `None`.
+    pub(crate) async fn send_activation_email(&self, to: &str, token: &str, lang: Option<Language>) -> Result<()> {
+        self.send(EmailKind::Verification, lang.unwrap_or(self.language), to, Some(token)).await
+    }
+
+    pub(crate) async fn send_password_reset_email(&self, to: &str, token: &str, lang: Option<Language>) -> Result<()> {
+        self.send(EmailKind::PasswordReset, lang.unwrap_or(self.language), to, Some(token)).await
+    }
+
+    pub(crate) async fn send_password_changed_email(&self, to: &str, lang: Option<Language>) -> Result<()> {
+        self.send(EmailKind::PasswordChanged, lang.unwrap_or(self.language), to, None).await
+    }
+
+    /// Sends a `kind` email written in `lang`, as a multipart message with a plain text and an HTML body.
+    pub(crate) async fn send(&self, kind: EmailKind, lang: Language, to: &str, token: Option<&str>) -> Result<()> {
+        let rendered = self.render(kind, lang, to, token)?;
+        let to = to.parse().map_err(|_| Error::InvalidEmailAddressError)?;
         let email = Message::builder()
-            .from(FROM_ADDRESS.parse().unwrap())
-            .to(to.parse().unwrap())
-            .subject(NEW_ACCOUNT_ACTIVATION_SUBJ)
-            .body(format!("To activate your account, use the following token: {}", token))
-            .unwrap();
-         match self.mailer.send(&email) {
-             Ok(_) => Ok(()),
-             Err(_) => Err(Error::SmtpRequestError),
-         }
+            .from(self.from.clone())
+            .to(to)
+            .subject(rendered.subject)
+            .multipart(
+                MultiPart::alternative()
+                    .singlepart(SinglePart::plain(rendered.text))
+                    .singlepart(SinglePart::html(rendered.html)),
+            )
+            .map_err(|_| Error::SmtpRequestError)?;
+        self.transport.send(email).await
+    }
 
+    pub(crate) fn render(&self, kind: EmailKind, lang: Language, to: &str, token: Option<&str>) -> Result<RenderedEmail> {
+        let url = match kind {
+            EmailKind::Verification => self.verification_url.as_ref(),
+            EmailKind::PasswordReset => self.password_reset_url.as_ref(),
+            EmailKind::PasswordChanged => None,
+        };
+        let context = EmailContext {
+            email: to.to_string(),
+            token: token.map(str::to_string),
+            link: url.zip(token).map(|(url, token)| format!("{}{}", url, token)),
+            language: lang,
+        };
+        template::render(self.templates.as_deref(), kind, &context)
     }
 }
 
-impl Default for Mailer {
-    fn default() -> Self {
-        let mailer = SmtpTransport::relay(SMTP_SERVER)
-            .unwrap()
-            .credentials(Credentials::new(SMTP_USERNAME.into(), SMTP_PASSWORD.into()))
-            .build();
-
-        Mailer { mailer }
+fn smtp_transport(config: &MailerConfig) -> Result<SmtpTransport> {
+    let builder = match config.tls {
+        TlsMode::None => Ok(SmtpTransport::builder_dangerous(config.host.as_str())),
+        TlsMode::StartTls => SmtpTransport::starttls_relay(&config.host),
+        TlsMode::Tls => SmtpTransport::relay(&config.host),
+    };
+    let mut builder = builder.map_err(|err| {
+        Error::ConfigurationError(format!("invalid SMTP server \"{}\": {}", config.host, err))
+    })?;
+    if let Some(port) = config.port {
+        builder = builder.port(port);
+    }
+    if let Some(credentials) = &config.credentials {
+        builder = builder.credentials(Credentials::new(
+            credentials.username.clone(),
+            credentials.password.clone(),
+        ));
     }
-}
\ No newline at end of file
+    Ok(builder.timeout(config.timeout).build())
+}
@@ -0,0 +1,189 @@
+use crate::language::Language;
+use crate::prelude::*;
+
+/// The emails sent by [`Users`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmailKind {
+    /// Sent on signup, with the token that verifies the account.
+    Verification,
+    /// Sent when a user asks to reset their password, with the token that allows it.
+    PasswordReset,
+    /// A security notice sent after the password of an account is changed.
+    PasswordChanged,
+}
+
+impl EmailKind {
+    /// The name of the email, such as `"verification"`. It is used in the names of email templates.
+    pub fn name(self) -> &'static str {
+        match self {
+            EmailKind::Verification => "verification",
+            EmailKind::PasswordReset => "password_reset",
+            EmailKind::PasswordChanged => "password_changed",
+        }
+    }
+}
+
+/// The values available to email templates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EmailContext {
+    /// The address the email is sent to.
+    pub email: String,
+    /// The token sent with verification and password reset emails.
+    pub token: Option<String>,
+    /// The configured url followed by the token, when the mailer has a url for this email.
+    pub link: Option<String>,
+    /// The language the email is written in.
+    pub language: Language,
+}
+
+/// The subject and the bodies of an email, ready to be sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+/// A set of templates used to write the emails sent by [`Users`]. It is implemented for
+/// [`tera::Tera`] with the `tera` feature, and for [`handlebars::Handlebars`] with the `handlebars` feature.
+///
+/// Every email is made of three templates named after the [`EmailKind`] and the [`Language`]:
+/// `<kind>.<language>.subject`, `<kind>.<language>.txt` and `<kind>.<language>.html`,
+/// for example `verification.es.html`. They are rendered with an [`EmailContext`],
+/// and the built-in text is used for the templates that aren't defined.
+/// ```rust
+/// # use rocket_auth_nosql::{Error, MailerConfig, MemoryTransport, Users};
+/// # fn func() -> Result<(), Error> {
+/// let mut tera = tera::Tera::default();
+/// tera.add_raw_templates(vec![
+///     ("verification.en.subject", "Welcome to Example"),
+///     ("verification.en.html", "<p>Welcome! <a href=\"{{ link }}\">Verify your account</a>.</p>"),
+/// ]).unwrap();
+///
+/// let config = MailerConfig {
+///     from: "accounts@example.com".into(),
+///     verification_url: Some("https://example.com/verify/".into()),
+///     ..Default::default()
+/// };
+/// let users = Users::open_memory()
+///     .with_mail_transport(config, MemoryTransport::new())?
+///     .with_email_templates(tera)?;
+/// # Ok(()) }
+/// ```
+pub trait EmailTemplates: Send + Sync {
+    /// Renders the template called `name`, or returns `None` if there is no such template.
+    fn render(&self, name: &str, context: &EmailContext) -> Result<Option<String>>;
+}
+
+/// The built-in text of an email, in one language.
+pub(crate) struct EmailText {
+    pub subject: &'static str,
+    pub body: &'static str,
+    /// The label of the link.
+    pub action: &'static str,
+    /// The sentence introducing the token, when there is no link.
+    pub token: &'static str,
+    pub footer: &'static str,
+}
+
+/// Renders `kind` with `templates`, falling back to the built-in text for the missing templates.
+pub(crate) fn render(
+    templates: Option<&dyn EmailTemplates>,
+    kind: EmailKind,
+    context: &EmailContext,
+) -> Result<RenderedEmail> {
+    let lookup = |part: &str| match templates {
+        Some(templates) => {
+            let name = format!("{}.{}.{}", kind.name(), context.language.code(), part);
+            templates.render(&name, context)
+        }
+        None => Ok(None),
+    };
+    let text = context.language.email(kind);
+    Ok(RenderedEmail {
+        subject: match lookup("subject")? {
+            Some(subject) => subject.trim().to_string(),
+            None => text.subject.to_string(),
+        },
+        text: match lookup("txt")? {
+            Some(body) => body,
+            None => default_text(&text, context),
+        },
+        html: match lookup("html")? {
+            Some(body) => body,
+            None => default_html(&text, context),
+        },
+    })
+}
+
+fn default_text(text: &EmailText, context: &EmailContext) -> String {
+    let mut body = format!("{}\n\n", text.body);
+    match (&context.link, &context.token) {
+        (Some(link), _) => body += &format!("{}:\n{}\n\n", text.action, link),
+        (None, Some(token)) => body += &format!("{}:\n{}\n\n", text.token, token),
+        (None, None) => {}
+    }
+    body + text.footer + "\n"
+}
+
+fn default_html(text: &EmailText, context: &EmailContext) -> String {
+    let mut body = format!("<p>{}</p>\n", escape_html(text.body));
+    match (&context.link, &context.token) {
+        (Some(link), _) => {
+            body += &format!("<p><a href=\"{}\">{}</a></p>\n", escape_html(link), escape_html(text.action))
+        }
+        (None, Some(token)) => {
+            body += &format!("<p>{}:<br>\n<strong>{}</strong></p>\n", escape_html(text.token), escape_html(token))
+        }
+        (None, None) => {}
+    }
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<body>\n{}<p>{}</p>\n</body>\n</html>\n",
+        context.language.code(),
+        body,
+        escape_html(text.footer)
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Templates ending in `.html` are escaped by Tera.
+#[cfg(feature = "tera")]
+impl EmailTemplates for tera::Tera {
+    fn render(&self, name: &str, context: &EmailContext) -> Result<Option<String>> {
+        if !self.get_template_names().any(|template| template == name) {
+            return Ok(None);
+        }
+        let context = tera::Context::from_serialize(context)
+            .map_err(|err| Error::TemplateError(err.to_string()))?;
+        tera::Tera::render(self, name, &context)
+            .map(Some)
+            .map_err(|err| Error::TemplateError(format!("{}: {}", name, err)))
+    }
+}
+
+/// Handlebars escapes every template, including the plain text ones, unless its escape function is changed.
+#[cfg(feature = "handlebars")]
+impl EmailTemplates for handlebars::Handlebars<'static> {
+    fn render(&self, name: &str, context: &EmailContext) -> Result<Option<String>> {
+        if !self.has_template(name) {
+            return Ok(None);
+        }
+        handlebars::Handlebars::render(self, name, context)
+            .map(Some)
+            .map_err(|err| Error::TemplateError(format!("{}: {}", name, err)))
+    }
+}
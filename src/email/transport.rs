@@ -0,0 +1,88 @@
+use crate::prelude::*;
+use lettre::{Message, Transport};
+use std::sync::{Arc, Mutex};
+
+/// A way of delivering emails. It is implemented for lettre's transports behind an [`Arc`],
+/// such as `Arc<SmtpTransport>`, `Arc<SendmailTransport>` and `Arc<FileTransport>`,
+/// as well as for [`MemoryTransport`].
+/// A custom transport can be set with [`Users::with_mail_transport`].
+///
+/// [`SmtpTransport`]: lettre::SmtpTransport
+/// [`SendmailTransport`]: lettre::SendmailTransport
+/// [`FileTransport`]: lettre::FileTransport
+#[rocket::async_trait]
+pub trait MailTransport: Send + Sync {
+    /// Delivers `message` to the recipients of its envelope.
+    async fn send(&self, message: Message) -> Result<()>;
+}
+
+/// The transport is shared with a blocking thread, so the async executor isn't stalled by it.
+#[rocket::async_trait]
+impl<T> MailTransport for Arc<T>
+where
+    T: Transport + Send + Sync + 'static,
+{
+    async fn send(&self, message: Message) -> Result<()> {
+        let transport = Arc::clone(self);
+        let sent = tokio::task::spawn_blocking(move || Transport::send(&*transport, &message).is_ok()).await;
+        match sent {
+            Ok(true) => Ok(()),
+            _ => Err(Error::SmtpRequestError),
+        }
+    }
+}
+
+/// An email captured by a [`MemoryTransport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SentEmail {
+    /// The addresses the email was sent to.
+    pub to: Vec<String>,
+    /// The full email, with its headers, as it would have been sent to the server.
+    pub message: String,
+}
+
+/// A transport that keeps every email in memory instead of sending it.
+/// It is meant for tests: clones share the same emails, so a clone can be
+/// given to [`Users`] while the original is used to inspect what was sent.
+/// ```rust
+/// # use rocket_auth_nosql::{Error, MailerConfig, MemoryTransport, Users};
+/// # fn func() -> Result<(), Error> {
+/// let transport = MemoryTransport::new();
+/// let config = MailerConfig {
+///     from: "accounts@example.com".into(),
+///     ..Default::default()
+/// };
+/// let users = Users::open_memory().with_mail_transport(config, transport.clone())?;
+/// assert!(transport.sent().is_empty());
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTransport {
+    sent: Arc<Mutex<Vec<SentEmail>>>,
+}
+
+impl MemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the emails sent so far, oldest first.
+    pub fn sent(&self) -> Vec<SentEmail> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// Forgets the emails sent so far.
+    pub fn clear(&self) {
+        self.sent.lock().unwrap().clear();
+    }
+}
+
+#[rocket::async_trait]
+impl MailTransport for MemoryTransport {
+    async fn send(&self, message: Message) -> Result<()> {
+        let to = message.envelope().to().iter().map(ToString::to_string).collect();
+        let message = String::from_utf8_lossy(&message.formatted()).into_owned();
+        self.sent.lock().unwrap().push(SentEmail { to, message });
+        Ok(())
+    }
+}
@@ -30,12 +30,33 @@ pub enum Error {
     /// This error occurs when a request to verify a client's email address contains an invalid verification token
     #[error("Invalid account verification token")]
     VerificationTokenMismatch,
+    /// This error occurs when a verification token is used after the end of its lifetime.
+    /// A new one can be sent with [`Users::resend_verification`](crate::Users::resend_verification).
+    #[error("The account verification token has expired")]
+    VerificationTokenExpired,
+    /// This error occurs when a new password matches one of the recent passwords of the user,
+    /// see [`PasswordPolicy::history`](crate::PasswordPolicy::history).
+    #[error("That password was used recently. Please choose another one.")]
+    PasswordReusedError,
+    /// This error occurs when a password reset token is unknown or has already been used.
+    #[error("Invalid password reset token")]
+    PasswordResetTokenMismatch,
+    /// This error occurs when a password reset token is used after the end of its lifetime.
+    #[error("The password reset token has expired")]
+    PasswordResetTokenExpired,
     /// This error occurs when the user has authenticated but the account is not verified
     #[error("Unverified email address")]
     UnverifiedError,
-    /// This error occurs when the SMTP server request encountered an error
+    /// This error occurs when the `auth` configuration is inconsistent, for example
+    /// when the database backend doesn't store users with the requested id type.
+    #[error("ConfigurationError: {0}")]
+    ConfigurationError(String),
+    /// This error occurs when an email could not be sent, for example because the SMTP server request encountered an error
     #[error("SMTP Transport Error")]
     SmtpRequestError,
+    /// This error occurs when an email template cannot be rendered.
+    #[error("TemplateError: {0}")]
+    TemplateError(String),
     /// A wrapper around [`validator::ValidationError`].
     #[error("{0}")]
     FormValidationError(#[from] validator::ValidationError),
@@ -57,6 +78,13 @@ pub enum Error {
     #[error("SerdeError: {0}")]
     SerdeError(#[from] serde_json::Error),
 
+    /// A wrapper around [`sqlx::Error`].
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    #[error("SqlxError: {0}")]
+    SqlxError(#[from] sqlx::Error),
+
+    /// A wrapper around [`mongodb::error::Error`].
+    #[cfg(feature = "mongodb")]
     #[error("MongoDBError")]
     MongoDBError(#[from] mongodb::error::Error)
 }
@@ -65,9 +93,14 @@ use self::Error::*;
 impl Error {
     fn message(&self) -> String {
         match self {
+            #[cfg(feature = "mongodb")]
             MongoDBError(err) => format!("{}", err),
             InvalidEmailAddressError
             | VerificationTokenMismatch
+            | VerificationTokenExpired
+            | PasswordResetTokenMismatch
+            | PasswordReusedError
+            | PasswordResetTokenExpired
             | EmailAlreadyExists
             | UnauthorizedError
             | SmtpRequestError
@@ -75,8 +108,7 @@ impl Error {
             FormValidationErrors(source) => {
                 source
                     .field_errors()
-                    .into_iter()
-                    .map(|(_, error)| error)
+                    .into_values()
                     .map(IntoIterator::into_iter)
                     .map(|errs| {
                         errs //
@@ -86,7 +118,7 @@ impl Error {
                     .fold(String::new(), |a, b| a + &b)
             }
             #[cfg(debug_assertions)]
-            e => return format!("{}", e),
+            e => format!("{}", e),
             #[allow(unreachable_patterns)]
             _ => "undefined".into(),
         }
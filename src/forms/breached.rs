@@ -0,0 +1,130 @@
+use crate::prelude::*;
+use sha1::{Digest, Sha1};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A local list of breached passwords, checked by [`PasswordPolicy::breached_passwords`]
+/// without any network access.
+///
+/// It is read from a [Pwned Passwords](https://haveibeenpwned.com/Passwords) dump of SHA-1 hashes
+/// ordered by hash, with one hexadecimal hash per line, optionally followed by `:` and a count,
+/// as in `5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:3861493`. The file stays on disk:
+/// a lookup is a binary search over its lines, which only reads a few dozen small blocks,
+/// so the full dump can be used without loading it in memory.
+/// ```rust,no_run
+/// # use rocket_auth_nosql::{BreachedPasswords, Error, PasswordPolicy, Users};
+/// # use std::sync::Arc;
+/// # fn func() -> Result<(), Error> {
+/// let breached = BreachedPasswords::open("pwned-passwords-sha1-ordered-by-hash.txt")?;
+/// let mut users = Users::open_memory();
+/// users.set_password_policy(PasswordPolicy {
+///     breached_passwords: Some(Arc::new(breached)),
+///     ..Default::default()
+/// });
+/// # Ok(()) }
+/// ```
+pub struct BreachedPasswords {
+    path: PathBuf,
+    file: Mutex<File>,
+    size: u64,
+}
+
+impl BreachedPasswords {
+    /// Opens the file at `path`, failing if it doesn't start with a SHA-1 hash.
+    /// The file isn't checked to be sorted, as that would mean reading all of it.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let error = |err: &dyn fmt::Display| {
+            Error::ConfigurationError(format!("invalid breached password list \"{}\": {}", path.display(), err))
+        };
+        let mut file = File::open(path).map_err(|err| error(&err))?;
+        let size = file.metadata().map_err(|err| error(&err))?.len();
+        let (first, _) = read_line(&mut file, 0).map_err(|err| error(&err))?;
+        if parse_hash(&first).is_none() {
+            return Err(error(&"the first line is not a SHA-1 hash"));
+        }
+        Ok(BreachedPasswords {
+            path: path.to_owned(),
+            file: Mutex::new(file),
+            size,
+        })
+    }
+
+    /// Whether `password` is in the list. A file that can't be read is logged, and treated as
+    /// not containing the password.
+    pub fn contains(&self, password: &str) -> bool {
+        let hash = format!("{:X}", Sha1::digest(password.as_bytes()));
+        self.search(&hash).unwrap_or_else(|err| {
+            log::warn!("Failed to read the breached password list \"{}\": {}", self.path.display(), err);
+            false
+        })
+    }
+
+    /// The path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Binary searches the lines of the file for `hash`, in uppercase.
+    /// A probe at any offset reads the first line starting at or after it.
+    fn search(&self, hash: &str) -> io::Result<bool> {
+        let mut file = self.file.lock().unwrap();
+        let (mut start, mut end) = (0, self.size);
+        while start < end {
+            let middle = start + (end - start) / 2;
+            let line_start = if middle == 0 {
+                0
+            } else {
+                let (_, next) = read_line(&mut file, middle - 1)?;
+                next
+            };
+            if line_start >= end {
+                end = middle;
+                continue;
+            }
+            let (line, next) = read_line(&mut file, line_start)?;
+            match parse_hash(&line).map(|line_hash| line_hash.as_str().cmp(hash)) {
+                Some(std::cmp::Ordering::Equal) => return Ok(true),
+                Some(std::cmp::Ordering::Less) => start = next,
+                Some(std::cmp::Ordering::Greater) => end = middle,
+                None => return Err(io::Error::new(io::ErrorKind::InvalidData, "a line is not a SHA-1 hash")),
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Two lists are equal if they were opened from the same path.
+impl PartialEq for BreachedPasswords {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl Eq for BreachedPasswords {}
+
+impl fmt::Debug for BreachedPasswords {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BreachedPasswords").field("path", &self.path).finish()
+    }
+}
+
+/// Reads the line starting at `offset`, returning it with the offset of the next line.
+fn read_line(file: &mut File, offset: u64) -> io::Result<(String, u64)> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut line = String::new();
+    let len = BufReader::with_capacity(128, file).read_line(&mut line)?;
+    Ok((line, offset + len as u64))
+}
+
+/// The uppercase hash of a line such as `5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:3861493`.
+fn parse_hash(line: &str) -> Option<String> {
+    let hash = line.trim().split(':').next()?;
+    if hash.len() != 40 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(hash.to_ascii_uppercase())
+}
@@ -1,3 +1,12 @@
+mod breached;
+mod policy;
+mod strength;
+
+pub use breached::BreachedPasswords;
+pub use policy::PasswordPolicy;
+pub use strength::PasswordStrength;
+pub(crate) use strength::estimate as estimate_strength;
+
 use crate::prelude::*;
 
 
@@ -14,20 +23,18 @@ pub struct Login {
 pub struct Signup {
     #[validate(email)]
     pub email: String,
-    #[validate(
-        custom = "is_long",
-        custom = "has_number",
-        custom = "has_lowercase",
-        custom = "has_uppercase"
-    )]
+    /// It is checked against the [`PasswordPolicy`] of [`Users`] on signup.
     pub(crate) password: String,
+    /// The language of the emails sent to the user. It defaults to the language of the mailer.
+    #[serde(default)]
+    pub language: Option<Language>,
 }
 impl Debug for Signup {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Signup {{ email: {:?}, password: \"*****\" }}",
-            self.email
+            "Signup {{ email: {:?}, password: \"*****\", language: {:?} }}",
+            self.email, self.language
         )
     }
 }
@@ -55,6 +62,7 @@ impl From<Login> for Signup {
         Self {
             email: form.email,
             password: form.password,
+            language: None,
         }
     }
 }
@@ -68,53 +76,8 @@ impl<T: Deref<Target = Signup>> From<T> for Login {
     }
 }
 
-pub(crate) fn is_secure(password: &str) -> Result<(), ValidationError> {
-    is_long(password)?;
-    has_uppercase(password)?;
-    has_lowercase(password)?;
-    has_number(password)?;
-    Ok(())
-}
-
-fn is_long(password: &str) -> Result<(), ValidationError> {
-    if password.len() < 8 {
-        return Err(ValidationError::new(
-            "The password must be at least 8 characters long.\n"
-        ));
-    }
-    Ok(())
-}
-#[allow(unreachable_code)]
-
-fn has_uppercase(password: &str) -> Result<(), ValidationError> {
-    for c in password.chars() {
-        if c.is_uppercase() {
-            return Ok(());
-        }
-    }
-    Err(ValidationError::new(
-        "The password must include at least one uppercase character.\n"
-    ))
-}
-#[allow(unreachable_code)]
-fn has_lowercase(password: &str) -> Result<(), ValidationError>  {
-    for c in password.chars() {
-        if c.is_lowercase() {
-            return Ok(());
-        }
-    }
-    Err(ValidationError::new(
-        "The password must include least one uppercase caracter.\n"
-    ))
-}
-#[allow(unreachable_code)]
-fn has_number(password: &str) -> Result<(), ValidationError> {
-    for c in password.chars() {
-        if c.is_numeric() {
-            return Ok(());
-        }
-    }
-    Err(ValidationError::new(
-        "The password has to contain at least one digit.\n"
-    ))
+/// Checks that `password` follows `policy`. It is the single validation path of new passwords,
+/// used on signup and by [`User::set_password`].
+pub(crate) fn is_secure(password: &str, email: &str, policy: &PasswordPolicy) -> Result<(), ValidationError> {
+    policy.check(password, email)
 }
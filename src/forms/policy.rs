@@ -0,0 +1,146 @@
+use crate::prelude::*;
+use super::estimate_strength;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The rules new passwords must follow, set with [`Users::set_password_policy`].
+/// They are checked on signup, and whenever a password is changed.
+/// ```rust
+/// # use rocket_auth_nosql::PasswordPolicy;
+/// // Require 12 characters including a symbol, and forbid reusing any of the last ten passwords.
+/// let policy = PasswordPolicy {
+///     min_length: 12,
+///     require_symbol: true,
+///     min_score: Some(3),
+///     banned_words: vec!["example".into(), "password".into()],
+///     history: 10,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    /// The minimum number of characters. It defaults to 8.
+    pub min_length: usize,
+    /// The maximum number of characters. It defaults to 128.
+    pub max_length: usize,
+    /// Whether an uppercase letter is required. It defaults to `true`.
+    pub require_uppercase: bool,
+    /// Whether a lowercase letter is required. It defaults to `true`.
+    pub require_lowercase: bool,
+    /// Whether a digit is required. It defaults to `true`.
+    pub require_digit: bool,
+    /// Whether a character that is neither a letter, a digit nor a space is required. It defaults to `false`.
+    pub require_symbol: bool,
+    /// Words that passwords cannot contain, ignoring case. It defaults to an empty list.
+    pub banned_words: Vec<String>,
+    /// Whether passwords cannot contain the email of the user, or the part before the `@`,
+    /// ignoring case. It defaults to `true`.
+    pub reject_email: bool,
+    /// The lowest [`PasswordStrength::score`] accepted, from 0 to 4, to reject passwords that
+    /// follow the other rules but are easy to guess, such as `Password1`. It defaults to `None`,
+    /// meaning the strength isn't checked.
+    pub min_score: Option<u8>,
+    /// Passwords found in a data breach, which cannot be used. It defaults to `None`.
+    /// It isn't read from the `password` section of the configuration, but opened from the path
+    /// given in [`AuthConfig::breached_passwords`](crate::AuthConfig::breached_passwords).
+    #[serde(skip)]
+    pub breached_passwords: Option<Arc<BreachedPasswords>>,
+    /// How many of the most recent passwords, including the current one, cannot be used again.
+    /// Older hashes are discarded, and `0` allows any password to be reused. It defaults to 4.
+    pub history: usize,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        PasswordPolicy {
+            min_length: 8,
+            max_length: 128,
+            require_uppercase: true,
+            require_lowercase: true,
+            require_digit: true,
+            require_symbol: false,
+            banned_words: Vec::new(),
+            reject_email: true,
+            min_score: None,
+            breached_passwords: None,
+            history: 4,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Checks that the password of the user with this `email` follows the policy.
+    pub(crate) fn check(&self, password: &str, email: &str) -> Result<(), ValidationError> {
+        let length = password.chars().count();
+        if length < self.min_length {
+            return Err(invalid(format!(
+                "The password must be at least {} characters long.\n",
+                self.min_length
+            )));
+        }
+        if length > self.max_length {
+            return Err(invalid(format!(
+                "The password must be at most {} characters long.\n",
+                self.max_length
+            )));
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            return Err(invalid("The password must include at least one uppercase character.\n"));
+        }
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            return Err(invalid("The password must include at least one lowercase character.\n"));
+        }
+        if self.require_digit && !password.chars().any(char::is_numeric) {
+            return Err(invalid("The password has to contain at least one digit.\n"));
+        }
+        if self.require_symbol && password.chars().all(|c| c.is_alphanumeric() || c.is_whitespace()) {
+            return Err(invalid("The password must include at least one symbol.\n"));
+        }
+        let lowercase = password.to_lowercase();
+        let banned = self
+            .banned_words
+            .iter()
+            .any(|word| !word.is_empty() && lowercase.contains(&word.to_lowercase()));
+        if banned {
+            return Err(invalid("The password contains a word that is not allowed.\n"));
+        }
+        if self.reject_email && contains_email(&lowercase, email) {
+            return Err(invalid("The password must not contain your email address.\n"));
+        }
+        if let Some(breached) = &self.breached_passwords {
+            if breached.contains(password) {
+                return Err(invalid("The password has appeared in a data breach. Please choose another one.\n"));
+            }
+        }
+        if let Some(min_score) = self.min_score {
+            let strength = estimate_strength(password, email);
+            if strength.score < min_score {
+                let mut message = String::from("The password is too easy to guess.\n");
+                for feedback in &strength.feedback {
+                    message += feedback;
+                    message += "\n";
+                }
+                return Err(invalid(message));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `password`, already in lowercase, contains `email` or its local part.
+/// Local parts shorter than three characters are too common to be rejected.
+fn contains_email(password: &str, email: &str) -> bool {
+    let email = email.to_lowercase();
+    let local = email.split('@').next().unwrap_or_default();
+    (!email.is_empty() && password.contains(&email)) || (local.chars().count() >= 3 && password.contains(local))
+}
+
+fn invalid(message: impl Into<String>) -> ValidationError {
+    let message = message.into();
+    ValidationError {
+        code: message.clone().into(),
+        message: Some(message.into()),
+        params: HashMap::new(),
+    }
+}
@@ -0,0 +1,256 @@
+use crate::prelude::*;
+
+/// An estimate of how hard a password is to guess, returned by [`Users::password_strength`].
+/// Like zxcvbn, it looks for common passwords, sequences, keyboard rows, repeated characters,
+/// years and the email of the user, and only counts the remaining characters as random.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PasswordStrength {
+    /// From 0, trivial to guess, to 4, very hard to guess.
+    pub score: u8,
+    /// The estimated number of guesses needed to find the password, as a power of two.
+    pub entropy: f64,
+    /// Suggestions to make the password stronger, empty for strong passwords.
+    pub feedback: Vec<String>,
+}
+
+/// The most common passwords and password words, most common first.
+const COMMON: &[&str] = &[
+    "password", "123456", "qwerty", "abc123", "letmein", "monkey", "dragon", "111111",
+    "baseball", "iloveyou", "trustno1", "sunshine", "master", "welcome", "shadow", "ashley",
+    "football", "jesus", "michael", "ninja", "mustang", "admin", "login", "princess",
+    "starwars", "whatever", "freedom", "secret", "hello", "charlie", "superman", "batman",
+    "passw0rd", "summer", "winter", "spring", "autumn", "flower", "hunter", "soccer",
+    "hockey", "killer", "george", "jordan", "harley", "ranger", "buster", "thomas",
+    "tigger", "robert", "access", "love", "pass", "test", "user", "guest",
+    "root", "changeme", "default", "computer", "internet", "cheese", "pepper", "ginger",
+    "orange", "banana", "apple", "chocolate", "maggie", "daniel", "andrew", "joshua",
+    "matrix", "silver", "golden", "diamond", "phoenix", "lovely", "angel", "baby",
+    "family", "friend", "money", "google", "facebook", "company", "welcome1", "qwertyuiop",
+];
+
+/// Keyboard rows, in which neighbouring keys are easy to type in a row.
+const KEYBOARD: &[&str] = &["qwertyuiop", "asdfghjkl", "zxcvbnm", "1234567890"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Pattern {
+    Common,
+    Email,
+    Sequence,
+    Keyboard,
+    Repeat,
+    Year,
+}
+
+struct Match {
+    len: usize,
+    entropy: f64,
+    pattern: Pattern,
+}
+
+/// Estimates the strength of the password of the user with this `email`.
+pub(crate) fn estimate(password: &str, email: &str) -> PasswordStrength {
+    let chars: Vec<char> = password.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+    let email = email.to_lowercase();
+    let mut words: Vec<(&str, usize, Pattern)> =
+        COMMON.iter().enumerate().map(|(rank, word)| (*word, rank, Pattern::Common)).collect();
+    for (rank, part) in email.split(|c: char| !c.is_alphanumeric()).enumerate() {
+        words.push((part, rank, Pattern::Email));
+    }
+
+    let random = (pool_size(&chars) as f64).log2();
+    let mut entropy = 0.0;
+    let mut patterns = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let found = [
+            match_word(&chars[i..], &lower[i..], &words),
+            match_sequence(&lower[i..]),
+            match_keyboard(&lower[i..]),
+            match_repeat(&lower[i..], random),
+            match_year(&lower[i..]),
+        ];
+        let best = found
+            .iter()
+            .flatten()
+            .filter(|m| m.entropy < random * m.len as f64)
+            .max_by(|a, b| a.len.cmp(&b.len).then(b.entropy.partial_cmp(&a.entropy).unwrap()));
+        match best {
+            Some(m) => {
+                entropy += m.entropy;
+                patterns.push(m.pattern);
+                i += m.len;
+            }
+            None => {
+                entropy += random;
+                i += 1;
+            }
+        }
+    }
+
+    let score = match entropy {
+        e if e < 10.0 => 0,
+        e if e < 20.0 => 1,
+        e if e < 27.0 => 2,
+        e if e < 33.0 => 3,
+        _ => 4,
+    };
+    PasswordStrength {
+        score,
+        entropy,
+        feedback: feedback(score, chars.len(), &patterns),
+    }
+}
+
+fn feedback(score: u8, len: usize, patterns: &[Pattern]) -> Vec<String> {
+    let mut feedback = Vec::new();
+    if score >= 3 {
+        return feedback;
+    }
+    let advice = [
+        (Pattern::Common, "Avoid common passwords and words."),
+        (Pattern::Email, "Avoid using your email address."),
+        (Pattern::Sequence, "Avoid sequences like abc or 123."),
+        (Pattern::Keyboard, "Avoid rows of keys like qwerty."),
+        (Pattern::Repeat, "Avoid repeated characters."),
+        (Pattern::Year, "Avoid years, such as dates of birth."),
+    ];
+    for (pattern, text) in advice.iter() {
+        if patterns.contains(pattern) {
+            feedback.push(text.to_string());
+        }
+    }
+    if len < 12 {
+        feedback.push("Use a longer password.".into());
+    }
+    feedback.push("Add a few uncommon words, or more random characters.".into());
+    feedback
+}
+
+/// The number of characters of the classes used in the password.
+fn pool_size(chars: &[char]) -> usize {
+    let mut pool = 0;
+    if chars.iter().any(|c| c.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_digit()) {
+        pool += 10;
+    }
+    if chars.iter().any(|c| c.is_ascii() && !c.is_ascii_alphanumeric()) {
+        pool += 33;
+    }
+    if chars.iter().any(|c| !c.is_ascii()) {
+        pool += 100;
+    }
+    pool.max(1)
+}
+
+/// Undoes common letter substitutions, such as `4` for `a`.
+fn unleet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '3' => 'e',
+        '1' | '!' => 'i',
+        '0' => 'o',
+        '5' | '$' => 's',
+        '7' => 't',
+        c => c,
+    }
+}
+
+fn match_word(chars: &[char], lower: &[char], words: &[(&str, usize, Pattern)]) -> Option<Match> {
+    words
+        .iter()
+        .filter(|(word, _, _)| word.chars().count() >= 3)
+        .filter_map(|(word, rank, pattern)| {
+            let len = word.chars().count();
+            if lower.len() < len {
+                return None;
+            }
+            let exact = word.chars().zip(lower).all(|(w, c)| w == *c);
+            let leet = word.chars().zip(lower).all(|(w, c)| w == unleet(*c));
+            if !exact && !leet {
+                return None;
+            }
+            let mut entropy = ((rank + 2) as f64).log2();
+            if chars[..len].iter().any(|c| c.is_uppercase()) {
+                entropy += 1.0;
+            }
+            if !exact {
+                entropy += 1.0;
+            }
+            Some(Match { len, entropy, pattern: *pattern })
+        })
+        .max_by_key(|m| m.len)
+}
+
+fn match_sequence(lower: &[char]) -> Option<Match> {
+    let step = (*lower.get(1)? as i64) - (*lower.first()? as i64);
+    if step.abs() != 1 || !lower[0].is_ascii_alphanumeric() {
+        return None;
+    }
+    let len = 1 + lower
+        .windows(2)
+        .take_while(|pair| pair[1] as i64 - pair[0] as i64 == step && pair[1].is_ascii_alphanumeric())
+        .count();
+    if len < 3 {
+        return None;
+    }
+    let start = if lower[0].is_ascii_digit() { 10.0 } else { 26.0_f64 };
+    let descending = if step < 0 { 1.0 } else { 0.0 };
+    Some(Match {
+        len,
+        entropy: start.log2() + (len as f64).log2() + descending,
+        pattern: Pattern::Sequence,
+    })
+}
+
+fn match_keyboard(lower: &[char]) -> Option<Match> {
+    KEYBOARD
+        .iter()
+        .filter_map(|row| {
+            let row: Vec<char> = row.chars().collect();
+            let start = row.iter().position(|key| Some(key) == lower.first())?;
+            let len = row[start..].iter().zip(lower).take_while(|(key, c)| key == c).count();
+            if len < 4 {
+                return None;
+            }
+            Some(Match {
+                len,
+                entropy: (row.len() as f64).log2() + (len as f64).log2() + 2.0,
+                pattern: Pattern::Keyboard,
+            })
+        })
+        .max_by_key(|m| m.len)
+}
+
+fn match_repeat(lower: &[char], random: f64) -> Option<Match> {
+    let first = lower.first()?;
+    let len = lower.iter().take_while(|c| *c == first).count();
+    if len < 3 {
+        return None;
+    }
+    Some(Match {
+        len,
+        entropy: random + (len as f64).log2(),
+        pattern: Pattern::Repeat,
+    })
+}
+
+fn match_year(lower: &[char]) -> Option<Match> {
+    if lower.len() < 4 || !lower[..4].iter().all(char::is_ascii_digit) {
+        return None;
+    }
+    let year: String = lower[..4].iter().collect();
+    match year.parse::<u32>() {
+        Ok(1900..=2099) => Some(Match {
+            len: 4,
+            entropy: 200.0_f64.log2(),
+            pattern: Pattern::Year,
+        }),
+        _ => None,
+    }
+}
@@ -1,22 +1,42 @@
-
 use super::*;
 
 use Error::*;
 
 impl Language {
-    pub fn en_message(error: Error) -> &'static str {
+    pub fn en_message(error: &Error) -> &'static str {
         match error {
-            UnsafePasswordTooShort => "The password has to be at least 8 characters long.",
-            UnauthenticatedClientError => "Client is not authenticated.",
+            UnauthenticatedError => "Client is not authenticated.",
             UnauthorizedError => "Unauthorized.",
-            InvalidCredentialsError => "Your email or password is incorrect.",
             UserNotFoundError => "User not found",
             InvalidEmailAddressError => "That email address is not valid.",
             EmailAlreadyExists => "That email already exists. Try logging in.",
             _ => "Internal server error."
         }
     }
+
+    pub(crate) fn en_email(kind: EmailKind) -> EmailText {
+        match kind {
+            EmailKind::Verification => EmailText {
+                subject: "Verify your account",
+                body: "Thank you for signing up. Please verify your email address to activate your account.",
+                action: "Verify my account",
+                token: "Your verification code is",
+                footer: "If you didn't create an account, you can ignore this email.",
+            },
+            EmailKind::PasswordReset => EmailText {
+                subject: "Reset your password",
+                body: "We received a request to reset the password of your account.",
+                action: "Choose a new password",
+                token: "Your password reset code is",
+                footer: "If you didn't ask to reset your password, you can ignore this email.",
+            },
+            EmailKind::PasswordChanged => EmailText {
+                subject: "Your password was changed",
+                body: "The password of your account was just changed.",
+                action: "",
+                token: "",
+                footer: "If you didn't change your password, reset it right away and contact us.",
+            },
+        }
+    }
 }
-  
-  
-    
\ No newline at end of file
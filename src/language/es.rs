@@ -1,21 +1,42 @@
-
 use super::*;
 
 use Error::*;
 
 impl Language {
-    pub fn es_message(error: Error) -> &'static str {
+    pub fn es_message(error: &Error) -> &'static str {
         match error {
-            UnsafePasswordTooShort => "La clave debe tener al menos 8 caracteres.",
-            UnauthenticatedClientError => "El cliente no esta autentificado.",
+            UnauthenticatedError => "El cliente no esta autentificado.",
             UnauthorizedError => "No autorizado.",
-            InvalidCredentialsError => "Su correo electónico o contraseña es incorrecta.",
             UserNotFoundError => "No se encotró el usuario.",
             InvalidEmailAddressError => "Correo inválido.",
             EmailAlreadyExists => "Ese correo ya existe.",
             _ => "Error interno del servidor."
         }
     }
+
+    pub(crate) fn es_email(kind: EmailKind) -> EmailText {
+        match kind {
+            EmailKind::Verification => EmailText {
+                subject: "Verifica tu cuenta",
+                body: "Gracias por registrarte. Verifica tu correo electrónico para activar tu cuenta.",
+                action: "Verificar mi cuenta",
+                token: "Tu código de verificación es",
+                footer: "Si no creaste una cuenta, puedes ignorar este correo.",
+            },
+            EmailKind::PasswordReset => EmailText {
+                subject: "Restablece tu contraseña",
+                body: "Recibimos una solicitud para restablecer la contraseña de tu cuenta.",
+                action: "Elegir una nueva contraseña",
+                token: "Tu código para restablecer la contraseña es",
+                footer: "Si no pediste restablecer tu contraseña, puedes ignorar este correo.",
+            },
+            EmailKind::PasswordChanged => EmailText {
+                subject: "Tu contraseña fue cambiada",
+                body: "La contraseña de tu cuenta acaba de ser cambiada.",
+                action: "",
+                token: "",
+                footer: "Si no cambiaste tu contraseña, restablécela de inmediato y contáctanos.",
+            },
+        }
+    }
 }
-  
-  
\ No newline at end of file
@@ -1,25 +1,60 @@
 
 
 use crate::prelude::*;
+use rocket::form::FromFormField;
+use crate::email::{EmailKind, EmailText};
 
 impl Error {
-    pub fn message(self, lang: Language) -> &'static str {
+    /// Returns a description of the error that can be shown to the client, written in `lang`.
+    pub fn message_in(&self, lang: Language) -> &'static str {
         lang.message(self)
     }
 }
 
-#[derive(Debug, Copy, Serialize, Deserialize, Clone)]
+/// The languages that error messages and emails are written in.
+/// Spanish and Portuguese are enabled with the `lang-es` and `lang-pt` features.
+/// In forms, languages are given by their code, such as `es`.
+#[derive(Debug, Default, Copy, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, FromFormField)]
+#[serde(rename_all = "lowercase")]
 pub enum Language {
+    #[default]
+    #[field(value = "en")]
     EN,
     #[cfg(feature="lang-es")]
+    #[field(value = "es")]
     ES,
     #[cfg(feature="lang-pt")]
+    #[field(value = "pt")]
     PT,
 }
 
 use Language::*;
 impl Language {
-    fn message(self, error: Error) -> &'static str {
+    /// The ISO 639-1 code of the language, such as `"en"`. It is used in the names of email templates.
+    pub fn code(self) -> &'static str {
+        match self {
+            EN => "en",
+            #[cfg(feature="lang-es")]
+            ES => "es",
+            #[cfg(feature="lang-pt")]
+            PT => "pt",
+        }
+    }
+
+    /// The language with this ISO 639-1 code, if it is enabled.
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    pub(crate) fn from_code(code: &str) -> Option<Language> {
+        match code {
+            "en" => Some(EN),
+            #[cfg(feature="lang-es")]
+            "es" => Some(ES),
+            #[cfg(feature="lang-pt")]
+            "pt" => Some(PT),
+            _ => None,
+        }
+    }
+
+    fn message(self, error: &Error) -> &'static str {
         match self {
             EN => Language::en_message(error),
             #[cfg(feature="lang-es")]
@@ -28,10 +63,20 @@ impl Language {
             PT => Language::pt_message(error),
         }
     }
+
+    pub(crate) fn email(self, kind: EmailKind) -> EmailText {
+        match self {
+            EN => Language::en_email(kind),
+            #[cfg(feature="lang-es")]
+            ES => Language::es_email(kind),
+            #[cfg(feature="lang-pt")]
+            PT => Language::pt_email(kind),
+        }
+    }
 }
 
 mod en;
 #[cfg(feature="lang-es")]
 mod es;
 #[cfg(feature="lang-pt")]
-mod pt;
\ No newline at end of file
+mod pt;
@@ -1,21 +1,42 @@
-
 use super::*;
 
 use Error::*;
 
 impl Language {
-    pub fn pt_message(error: Error) -> &'static str {
+    pub fn pt_message(error: &Error) -> &'static str {
         match error {
-            UnsafePasswordTooShort => "A senha deve ter pelo menos 8 caracteres.",
-            UnauthenticatedClientError => "O cliente não está autenticado.",
+            UnauthenticatedError => "O cliente não está autenticado.",
             UnauthorizedError => "Não autorizado.",
-            InvalidCredentialsError => "Seu correio eletrônico ou senha está incorreta.",
             UserNotFoundError => "O usuário não foi encontrado.",
             InvalidEmailAddressError => "O correio eletrônico não é válido.",
             EmailAlreadyExists => "Esse email já existe.",
             _ => "Erro interno do servidor."
         }
     }
+
+    pub(crate) fn pt_email(kind: EmailKind) -> EmailText {
+        match kind {
+            EmailKind::Verification => EmailText {
+                subject: "Verifique sua conta",
+                body: "Obrigado por se cadastrar. Verifique seu email para ativar sua conta.",
+                action: "Verificar minha conta",
+                token: "Seu código de verificação é",
+                footer: "Se você não criou uma conta, pode ignorar este email.",
+            },
+            EmailKind::PasswordReset => EmailText {
+                subject: "Redefina sua senha",
+                body: "Recebemos um pedido para redefinir a senha da sua conta.",
+                action: "Escolher uma nova senha",
+                token: "Seu código para redefinir a senha é",
+                footer: "Se você não pediu para redefinir sua senha, pode ignorar este email.",
+            },
+            EmailKind::PasswordChanged => EmailText {
+                subject: "Sua senha foi alterada",
+                body: "A senha da sua conta acabou de ser alterada.",
+                action: "",
+                token: "",
+                footer: "Se você não alterou sua senha, redefina-a imediatamente e entre em contato conosco.",
+            },
+        }
+    }
 }
-  
-  
\ No newline at end of file
@@ -2,7 +2,12 @@
 //! It lets you create, delete, and authenticate users.
 //! The available features are:
 //! * `redis`: for storing sessions on a redis server using `redis`.
-//! * `mongodb`: for interacting with a MongoDB database using `mongodb`.
+//! * `mongodb`: for interacting with a MongoDB database using `mongodb`. It is enabled by default.
+//! * `sqlite`: for interacting with a SQLite database using `sqlx`.
+//! * `postgres`: for interacting with a PostgreSQL database using `sqlx`.
+//! * `tera`: for writing emails with [`tera`](https://docs.rs/tera) templates.
+//! * `handlebars`: for writing emails with [`handlebars`](https://docs.rs/handlebars) templates.
+//! * `lang-es` and `lang-pt`: for error messages and emails in Spanish and Portuguese.
 //!
 //!
 //! `rocket_auth_nosql` uses private cookies to store session data.
@@ -12,7 +17,7 @@
 //!
 //!
 //! To use `rocket_auth_nosql` include it as a dependency in your Cargo.toml file:
-//! ```
+//! ```toml
 //! [dependencies.rocket_auth_nosql]
 //! version = "0.0.1"
 //! ```
@@ -58,12 +63,12 @@
 //! }
 //!
 //! #[get("/logout")]
-//! fn logout(auth: Auth<'_>) {
-//!     auth.logout();
+//! async fn logout(auth: Auth<'_>) {
+//!     auth.logout().await;
 //! }
 //! #[tokio::main]
 //! async fn main() -> Result<(), Error>{
-//!     let users = Users::open_sqlite("mydb.db").await?;
+//!     let users = Users::open_mongodb("mongodb://localhost:27017", "rocket_auth_nosql").await?;
 //!
 //!     rocket::build()
 //!         .mount("/", routes![signup, login, logout])
@@ -87,15 +92,16 @@
 //! use rocket_auth_nosql::Users;
 //!
 //! #[get("/see-user/<id>")]
-//! async fn see_user(id: i32, users: &State<Users>) -> String {
+//! async fn see_user(id: i64, users: &State<Users<i64>>) -> String {
 //!     let user = users.get_by_id(id).await.unwrap();
 //!     format!("{}", json!(user))
 //! }
 //! # fn main() {}
 //! ```
 //!
-//! A [`Users`] instance can be constructed by connecting it to the database with the methods [`open_sqlite`](Users::open_sqlite),
-//! [`open_postgres`](Users::open_postgres) or [`open_rusqlite`](Users::open_rusqlite). Furthermore, it can be constructed from a working connection.
+//! A [`Users`] instance can be constructed by connecting it to the database with the methods [`open_mongodb`](Users::open_mongodb),
+//! [`open_sqlite`](Users::open_sqlite), [`open_postgres`](Users::open_postgres) or [`open_memory`](Users::open_memory). Furthermore, it can be constructed from a working connection.
+//! Alternatively, the [`AuthFairing`] builds it from the `[auth]` section of the Rocket configuration, described in [`AuthConfig`].
 //!
 //!
 //! ## User guard
@@ -132,11 +138,15 @@
 //! ```
 
 
+mod config;
 mod cookies;
 mod db;
 mod email;
 mod error;
+// The `FromForm` derive allows the `private_in_public` lint, which newer compilers have removed.
+#[allow(renamed_and_removed_lints)]
 mod forms;
+mod language;
 pub mod prelude;
 mod session;
 mod user;
@@ -144,14 +154,48 @@ mod user;
 #[cfg(test)]
 mod tests;
 
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::str::FromStr;
 
 pub use prelude::*;
 pub use crate::user::auth::Auth;
 pub use cookies::Session;
+pub use email::{
+    EmailContext, EmailKind, EmailTemplates, MailTransport, MailerConfig, MemoryTransport, RenderedEmail, SentEmail,
+    SmtpCredentials, TlsMode, TransportKind,
+};
 pub use error::Error;
+pub use config::{AuthConfig, AuthFairing, ConfigUserId, DatabaseConfig, SessionsConfig};
+pub use session::reaper::SessionReaper;
+pub use user::verification::VerifyAccount;
+#[cfg(feature = "redis")]
+pub use session::redis::{RedisOptions, RedisPool};
 use crate::email::Mailer;
-use mongodb::bson::{oid::ObjectId};
+use serde::de::DeserializeOwned;
+
+/// The type used to identify users, both in the database and in the session store.
+/// It is implemented for every type with the required bounds,
+/// so backends can use integers, strings, UUIDs or MongoDB's `ObjectId`.
+pub trait UserId:
+    'static + Send + Sync + Clone + Debug + Display + FromStr + Eq + Hash + Ord + Serialize + DeserializeOwned
+{
+}
+
+impl<T> UserId for T where
+    T: 'static + Send + Sync + Clone + Debug + Display + FromStr + Eq + Hash + Ord + Serialize + DeserializeOwned
+{
+}
+
+/// The [`UserId`] used when none is specified.
+/// It is MongoDB's `ObjectId` when the `mongodb` feature is enabled, and `i64` otherwise.
+#[cfg(feature = "mongodb")]
+pub type DefaultUserId = mongodb::bson::oid::ObjectId;
+
+/// The [`UserId`] used when none is specified.
+/// It is MongoDB's `ObjectId` when the `mongodb` feature is enabled, and `i64` otherwise.
+#[cfg(not(feature = "mongodb"))]
+pub type DefaultUserId = i64;
 
 /// The `User` guard can be used to restrict content so it can only be viewed by authenticated users.
 /// ```rust
@@ -165,17 +209,30 @@ use mongodb::bson::{oid::ObjectId};
 /// # fn main() {}
 /// ```
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Hash, PartialOrd, Ord)]
-pub struct User {
+pub struct User<Id = DefaultUserId> {
 	#[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
-    pub id: Option<ObjectId>,
+    pub id: Option<Id>,
     email: String,
     pub is_admin: bool,
     is_verified: bool,
+    /// The SHA-256 hash of the account verification token, empty once the account is verified.
     verification_token: String,
+    /// The Unix time, in seconds, at which `verification_token` was issued.
+    #[serde(default)]
+    verification_issued_at: i64,
     password: String,
-    prev_password: Option<String>,
-    prev_password_1: Option<String>,
-    prev_password_2: Option<String>,
+    /// The SHA-256 hash of the pending password reset token, if any.
+    #[serde(default)]
+    password_reset_hash: Option<String>,
+    /// The Unix time, in seconds, at which the password reset token was issued.
+    #[serde(default)]
+    password_reset_issued_at: i64,
+    /// The hashes of the previous passwords, most recent first, bounded by [`PasswordPolicy::history`].
+    #[serde(default)]
+    password_history: Vec<String>,
+    /// The language of the emails sent to the user, or the language of the mailer if it is `None`.
+    #[serde(default)]
+    language: Option<Language>,
 }
 
 /// The [`AdminUser`] guard can be used analogously to [`User`].
@@ -189,7 +246,7 @@ pub struct User {
 /// }
 /// ```
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Hash, PartialOrd, Ord)]
-pub struct AdminUser(User);
+pub struct AdminUser<Id = DefaultUserId>(User<Id>);
 
 /// The [`UnverifiedUser`] guard can be used analogously to [`User`].
 /// It is restricted to content for users that have authenticated
@@ -203,17 +260,23 @@ pub struct AdminUser(User);
 /// }
 /// ```
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Hash, PartialOrd, Ord)]
-pub struct UnverifiedUser(User);
+pub struct UnverifiedUser<Id = DefaultUserId>(User<Id>);
 
-impl Debug for AdminUser {
+impl<Id: Debug> Debug for AdminUser<Id> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Admin{:?}", self.0)
     }
 }
 
 /// The `Users` struct is used to query users from the database, as well as to create, modify and delete them.
-pub struct Users {
-    conn: Box<dyn DBConnection>,
-    sess: Box<dyn SessionManager>,
-    mailer: Option<Box<Mailer>>,
+/// Its type parameter is the [`UserId`] type used by the database backend.
+/// It defaults to [`DefaultUserId`]. The MongoDB backend uses `ObjectId`, while the SQL and in-memory backends use `i64`.
+pub struct Users<Id = DefaultUserId> {
+    conn: std::sync::Arc<dyn DBConnection<Id = Id>>,
+    sess: std::sync::Arc<dyn SessionManager<Id>>,
+    policy: SessionPolicy,
+    verification: VerificationPolicy,
+    password_policy: PasswordPolicy,
+    cookie: CookieConfig,
+    mailer: Option<std::sync::Arc<Mailer>>,
 }
@@ -1,8 +1,10 @@
-// pub use crate::language::Language;
-pub use crate::cookies::Session;
+pub use crate::language::Language;
+pub use crate::cookies::{CookieConfig, Session};
+pub use crate::session::{SessionInfo, SessionPolicy};
+pub use crate::user::verification::VerificationPolicy;
 pub use crate::error::Error;
-pub use crate::forms::{Login, Signup};
-pub use crate::{AdminUser, UnverifiedUser, Auth, User, Users};
+pub use crate::forms::{BreachedPasswords, Login, PasswordPolicy, PasswordStrength, Signup};
+pub use crate::{AdminUser, UnverifiedUser, Auth, DefaultUserId, User, UserId, Users};
 /// A type alias of result to omit the error type. 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -1,44 +1,105 @@
-use super::AuthKey;
+use super::{AuthKey, SessionInfo};
 use super::SessionManager;
 use crate::prelude::*;
-use chashmap::CHashMap;
-use mongodb::bson::{oid::ObjectId};
+use std::collections::HashMap;
+use std::sync::RwLock;
 
-impl SessionManager for CHashMap<ObjectId, AuthKey> {
+/// The default session store. It keeps the sessions of every user in memory,
+/// indexed by the user id and then by the session id.
+pub struct MemorySessions<Id> {
+    users: RwLock<HashMap<Id, HashMap<String, AuthKey>>>,
+}
+
+impl<Id: UserId> MemorySessions<Id> {
+    pub fn new() -> Self {
+        MemorySessions {
+            users: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn insert_key(&self, id: Id, session_id: &str, key: AuthKey) {
+        let mut users = self.users.write().unwrap();
+        users.entry(id).or_default().insert(session_id.into(), key);
+    }
+}
+
+impl<Id: UserId> Default for MemorySessions<Id> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-    fn insert(&self, id: ObjectId, key: String) -> Result<()> {
-        self.insert(id, key.into());
+#[rocket::async_trait]
+impl<Id: UserId> SessionManager<Id> for MemorySessions<Id> {
+
+    async fn refresh(&self, id: Id, session_id: &str, time: Duration) -> Result<()> {
+        let mut users = self.users.write().unwrap();
+        let key = users.get_mut(&id).and_then(|sessions| sessions.get_mut(session_id));
+        if let Some(key) = key {
+            if !key.is_expired() {
+                key.expires = now() + time.as_secs() as i64;
+            }
+        }
         Ok(())
     }
 
-    fn remove(&self, id: ObjectId) -> Result<()> {
-        self.remove(&id);
+    async fn remove(&self, id: Id, session_id: &str) -> Result<()> {
+        let mut users = self.users.write().unwrap();
+        if let Some(sessions) = users.get_mut(&id) {
+            sessions.remove(session_id);
+            if sessions.is_empty() {
+                users.remove(&id);
+            }
+        }
         Ok(())
     }
 
-    fn get(&self, id: ObjectId) -> Option<String> {
-        let key = self.get(&id)?;
+    async fn remove_all(&self, id: Id) -> Result<()> {
+        self.users.write().unwrap().remove(&id);
+        Ok(())
+    }
+
+    async fn get(&self, id: Id, session_id: &str) -> Option<String> {
+        let users = self.users.read().unwrap();
+        let key = users.get(&id)?.get(session_id)?;
+        if key.is_expired() {
+            return None;
+        }
         Some(key.secret.clone())
     }
 
-    fn clear_all(&self) -> Result<()> {
-        self.clear();
+    async fn list(&self, id: Id) -> Result<Vec<SessionInfo>> {
+        let users = self.users.read().unwrap();
+        let sessions = match users.get(&id) {
+            Some(sessions) => sessions,
+            None => return Ok(vec![]),
+        };
+        let sessions = sessions
+            .iter()
+            .filter(|(_, auth_key)| !auth_key.is_expired())
+            .map(|(session_id, auth_key)| SessionInfo {
+                session_id: session_id.clone(),
+                expires: auth_key.expires,
+            })
+            .collect();
+        Ok(sessions)
+    }
+
+    async fn clear_all(&self) -> Result<()> {
+        self.users.write().unwrap().clear();
         Ok(())
     }
 
-    fn insert_for(&self, id: ObjectId, key: String, time: Duration) -> Result<()>  {
-        let key = AuthKey {
-            expires: time.as_secs() as i64,
-            secret: key,
-        };
-        self.insert(id, key);
+    async fn insert_for(&self, id: Id, session_id: &str, key: String, time: Duration) -> Result<()>  {
+        self.insert_key(id, session_id, AuthKey::expiring_in(key, time));
         Ok(())
     }
 
-    fn clear_expired(&self) -> Result<()> {
-        let time = now();
-        self.retain(|_, auth_key| auth_key.expires > time);
+    async fn clear_expired(&self) -> Result<()> {
+        self.users.write().unwrap().retain(|_, sessions| {
+            sessions.retain(|_, auth_key| !auth_key.is_expired());
+            !sessions.is_empty()
+        });
         Ok(())
     }
 }
-
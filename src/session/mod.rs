@@ -1,30 +1,108 @@
 use crate::prelude::*;
 use std::time::Duration;
 pub mod default;
-use mongodb::bson::{oid::ObjectId};
+pub mod reaper;
 #[cfg(feature = "redis")]
 pub mod redis;
 
+const YEAR_IN_SECS: i64 = 365 * 60 * 60 * 24;
 
-pub trait SessionManager: Send + Sync {
-    fn insert(&self, id: ObjectId, key: String) -> Result<()>;
-    fn insert_for(&self, id: ObjectId, key: String, time: Duration) -> Result<()>;
-    fn remove(&self, id: ObjectId) -> Result<()>;
-    fn get(&self, id: ObjectId) -> Option<String>;
-    fn clear_all(&self) -> Result<()>;
-    fn clear_expired(&self) -> Result<()>;
+
+/// A store for the authentication keys of user sessions.
+/// Each session is identified by its own session id, so a user can hold several sessions at once.
+#[rocket::async_trait]
+pub trait SessionManager<Id>: Send + Sync {
+    async fn insert_for(&self, id: Id, session_id: &str, key: String, time: Duration) -> Result<()>;
+    async fn refresh(&self, id: Id, session_id: &str, time: Duration) -> Result<()>;
+    async fn remove(&self, id: Id, session_id: &str) -> Result<()>;
+    async fn remove_all(&self, id: Id) -> Result<()>;
+    async fn get(&self, id: Id, session_id: &str) -> Option<String>;
+    async fn list(&self, id: Id) -> Result<Vec<SessionInfo>>;
+    async fn clear_all(&self) -> Result<()>;
+    async fn clear_expired(&self) -> Result<()>;
+    /// Whether the store deletes expired sessions by itself, in which case [`SessionReaper`](crate::SessionReaper) leaves it alone.
+    fn expires_sessions(&self) -> bool {
+        false
+    }
+}
+
+/// A description of one of the active sessions of a user, as returned by [`Users::list_sessions`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SessionInfo {
+    /// The id of the session. It matches [`Session::session_id`] for the client holding it.
+    pub session_id: String,
+    /// The Unix time, in seconds, at which the session expires.
+    pub expires: i64,
+}
+
+/// The limits on the length of a session, set with [`Users::set_session_policy`].
+/// ```rust
+/// # use rocket_auth_nosql::SessionPolicy;
+/// # use std::time::Duration;
+/// // Log users out after 15 minutes of inactivity, or after 8 hours in any case.
+/// let policy = SessionPolicy {
+///     idle_timeout: Some(Duration::from_secs(15 * 60)),
+///     max_lifetime: Duration::from_secs(8 * 60 * 60),
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SessionPolicy {
+    /// How long a session stays valid without authenticated requests.
+    /// Every request that retrieves the [`Auth`] guard, or a guard built on it such as [`User`], extends it.
+    /// It defaults to `None`, meaning sessions only end at the end of their lifetime.
+    #[serde(deserialize_with = "crate::config::deserialize_optional_secs")]
+    pub idle_timeout: Option<Duration>,
+    /// How long a session lasts after logging in, no matter how active it is.
+    /// [`Auth::login_for`] overrides it for a single session. It defaults to one year.
+    #[serde(deserialize_with = "crate::config::deserialize_secs")]
+    pub max_lifetime: Duration,
+}
+
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        SessionPolicy {
+            idle_timeout: None,
+            max_lifetime: Duration::from_secs(YEAR_IN_SECS as u64),
+        }
+    }
+}
+
+impl SessionPolicy {
+    /// How long the session store should keep a session, when at most `remaining` is left of its lifetime.
+    pub(crate) fn ttl(&self, remaining: Duration) -> Duration {
+        match self.idle_timeout {
+            Some(idle_timeout) => idle_timeout.min(remaining),
+            None => remaining,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthKey {
+    /// The Unix time, in seconds, after which the key is no longer valid.
     expires: i64,
     secret: String,
 }
 
+impl AuthKey {
+    /// Creates a key that expires once `time` has elapsed.
+    pub fn expiring_in(secret: String, time: Duration) -> AuthKey {
+        AuthKey {
+            expires: now() + time.as_secs() as i64,
+            secret,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires <= now()
+    }
+}
+
 impl From<String> for AuthKey {
     fn from(secret: String) -> AuthKey {
         AuthKey {
-            expires: 31536000,
+            expires: now() + YEAR_IN_SECS,
             secret
         }
     }
@@ -33,7 +111,7 @@ impl From<String> for AuthKey {
 impl From<&str> for AuthKey {
     fn from(secret: &str) -> AuthKey {
         AuthKey {
-            expires: 31536000,
+            expires: now() + YEAR_IN_SECS,
             secret: secret.into()
         }
     }
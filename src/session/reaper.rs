@@ -0,0 +1,80 @@
+use crate::prelude::*;
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::{Build, Orbit, Rocket};
+use std::marker::PhantomData;
+
+/// A fairing that periodically removes expired sessions from the session store of the managed [`Users`].
+/// Expired sessions are never accepted, so the sweep only reclaims the memory they use.
+/// Stores that expire sessions on their own, such as redis, are left untouched.
+/// The sweep stops when Rocket shuts down.
+/// ```rust,no_run
+/// # use rocket_auth_nosql::{Error, SessionReaper, Users};
+/// # use std::time::Duration;
+/// # async fn func() -> Result<(), Error> {
+/// let users = Users::open_memory();
+///
+/// rocket::build()
+///     .manage(users)
+///     .attach(SessionReaper::<i64>::new(Duration::from_secs(10 * 60)))
+///     .launch();
+/// # Ok(()) }
+/// ```
+pub struct SessionReaper<Id = DefaultUserId> {
+    interval: Duration,
+    _id: PhantomData<fn() -> Id>,
+}
+
+impl<Id: UserId> SessionReaper<Id> {
+    /// Creates a fairing that sweeps the session store every `interval`.
+    /// The interval is rounded up to one second.
+    pub fn new(interval: Duration) -> Self {
+        SessionReaper {
+            interval: interval.max(Duration::from_secs(1)),
+            _id: PhantomData,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<Id: UserId> Fairing for SessionReaper<Id> {
+    fn info(&self) -> Info {
+        Info {
+            name: "Session reaper",
+            kind: Kind::Ignite | Kind::Liftoff,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        if rocket.state::<Users<Id>>().is_some() {
+            Ok(rocket)
+        } else {
+            log::error!("SessionReaper: {}", Error::UnmanagedStateError);
+            Err(rocket)
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        let sess = match rocket.state::<Users<Id>>() {
+            Some(users) if !users.sess.expires_sessions() => users.sess.clone(),
+            _ => return,
+        };
+        let period = self.interval;
+        let shutdown = rocket.shutdown();
+        tokio::spawn(async move {
+            let sweep = async {
+                let mut interval = tokio::time::interval(period);
+                loop {
+                    interval.tick().await;
+                    // A failed sweep is retried on the next tick.
+                    if let Err(error) = sess.clear_expired().await {
+                        log::warn!("SessionReaper: failed to remove expired sessions: {}", error);
+                    }
+                }
+            };
+            tokio::select! {
+                _ = sweep => {}
+                _ = shutdown => {}
+            }
+        });
+    }
+}
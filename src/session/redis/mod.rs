@@ -1,42 +1,236 @@
-use super::SessionManager;
+use super::{SessionInfo, SessionManager};
 use crate::prelude::*;
 
-use redis::{Client, Commands};
-use mongodb::bson::oid::ObjectId;
+use redis::aio::MultiplexedConnection;
+use redis::{AsyncCommands, Client, RedisResult};
+use std::future::Future;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::Mutex;
 
-const YEAR_IN_SECS: usize = 365 * 60 * 60 * 24;
+/// The options used to configure the pooled redis session store.
+/// ```rust,no_run
+/// # use rocket_auth_nosql::{Error, RedisOptions, Users};
+/// # use std::time::Duration;
+/// # async fn func(mut users: Users) -> Result<(), Error> {
+/// let options = RedisOptions {
+///     pool_size: 8,
+///     response_timeout: Duration::from_millis(500),
+///     ..RedisOptions::new("redis://127.0.0.1/")
+/// };
+/// users.open_redis_with(options)?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RedisOptions {
+    /// The url of the redis server. It defaults to `redis://127.0.0.1/`.
+    pub url: String,
+    /// The number of multiplexed connections shared by all requests. It defaults to `4`.
+    pub pool_size: usize,
+    /// How long to wait for a connection to be established. It defaults to five seconds.
+    #[serde(deserialize_with = "crate::config::deserialize_secs")]
+    pub connection_timeout: Duration,
+    /// How long to wait for the response to a command. It defaults to two seconds.
+    #[serde(deserialize_with = "crate::config::deserialize_secs")]
+    pub response_timeout: Duration,
+    /// The prefix of every key written by the session store. It defaults to `rocket_auth_nosql:session:`.
+    /// It lets sessions share a redis database with other data, since only keys with this prefix
+    /// are ever read or deleted. Note that with an empty prefix, clearing the sessions deletes every key in the database.
+    pub key_prefix: String,
+}
+
+impl RedisOptions {
+    /// Creates the default options for the redis server at `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        RedisOptions {
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for RedisOptions {
+    fn default() -> Self {
+        RedisOptions {
+            url: "redis://127.0.0.1/".into(),
+            pool_size: 4,
+            connection_timeout: Duration::from_secs(5),
+            response_timeout: Duration::from_secs(2),
+            key_prefix: "rocket_auth_nosql:session:".into(),
+        }
+    }
+}
+
+/// A redis session store that reuses its connections across requests.
+/// It keeps up to [`pool_size`](RedisOptions::pool_size) multiplexed connections,
+/// which are opened lazily and reopened if they are dropped.
+pub struct RedisPool {
+    client: Client,
+    options: RedisOptions,
+    slots: Vec<Mutex<Option<MultiplexedConnection>>>,
+    next: AtomicUsize,
+}
+
+impl RedisPool {
+    /// Creates a pool for the redis server described by `options`.
+    /// No connection is opened until the first session is queried.
+    pub fn open(options: RedisOptions) -> Result<Self> {
+        let client = Client::open(options.url.as_str())?;
+        Ok(Self::from_client(client, options))
+    }
+
+    pub(crate) fn from_client(client: Client, options: RedisOptions) -> Self {
+        let slots = (0..options.pool_size.max(1)).map(|_| Mutex::new(None)).collect();
+        RedisPool {
+            client,
+            options,
+            slots,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    async fn connection(&self, index: usize) -> RedisResult<MultiplexedConnection> {
+        let mut slot = self.slots[index].lock().await;
+        if let Some(cnn) = &*slot {
+            return Ok(cnn.clone());
+        }
+        let connect = self.client.get_multiplexed_tokio_connection();
+        let cnn = with_timeout(self.options.connection_timeout, connect).await?;
+        *slot = Some(cnn.clone());
+        Ok(cnn)
+    }
+
+    fn session_key<Id: UserId>(&self, id: &Id, session_id: &str) -> String {
+        format!("{}{}:{}", self.options.key_prefix, id, session_id)
+    }
+
+    async fn query<T, F, Fut>(&self, command: F) -> RedisResult<T>
+    where
+        F: FnOnce(MultiplexedConnection) -> Fut,
+        Fut: Future<Output = RedisResult<T>>,
+    {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.slots.len();
+        let cnn = self.connection(index).await?;
+        let result = with_timeout(self.options.response_timeout, command(cnn)).await;
+        if let Err(err) = &result {
+            if err.is_io_error() || err.is_timeout() || err.is_connection_dropped() {
+                // The connection will be reopened by the next query that uses this slot.
+                *self.slots[index].lock().await = None;
+            }
+        }
+        result
+    }
+}
+
+async fn with_timeout<T>(time: Duration, future: impl Future<Output = RedisResult<T>>) -> RedisResult<T> {
+    match tokio::time::timeout(time, future).await {
+        Ok(result) => result,
+        Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, "redis request timed out").into()),
+    }
+}
 
-impl SessionManager for Client {
+/// Collects every key matching `pattern`, using `SCAN` so the server is never blocked.
+async fn scan_keys(cnn: &mut MultiplexedConnection, pattern: String) -> RedisResult<Vec<String>> {
+    let mut keys = vec![];
+    let mut iter = cnn.scan_match(pattern).await?;
+    while let Some(key) = iter.next_item().await {
+        keys.push(key);
+    }
+    Ok(keys)
+}
 
-    fn insert(&self, id: ObjectId, key: String) -> Result<()> {
-        let mut cnn = self.get_connection()?;
-        cnn.set_ex(&id.bytes(), key, YEAR_IN_SECS)?;
+/// Escapes the glob characters of `prefix`, and returns a pattern matching every key that starts with it.
+fn prefix_pattern(prefix: &str) -> String {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('*');
+    pattern
+}
+
+/// Lists the sessions whose keys start with `prefix`, along with their expiry dates.
+async fn list_sessions(mut cnn: MultiplexedConnection, prefix: String) -> RedisResult<Vec<SessionInfo>> {
+    let keys = scan_keys(&mut cnn, prefix_pattern(&prefix)).await?;
+    let mut sessions = vec![];
+    for key in keys {
+        let ttl: i64 = cnn.ttl(&key).await?;
+        // A negative ttl means the key expired since it was scanned.
+        if ttl < 0 {
+            continue;
+        }
+        sessions.push(SessionInfo {
+            session_id: key[prefix.len()..].into(),
+            expires: now() + ttl,
+        });
+    }
+    Ok(sessions)
+}
+
+/// Deletes every key matching `pattern`.
+async fn delete_matching(mut cnn: MultiplexedConnection, pattern: String) -> RedisResult<()> {
+    let keys = scan_keys(&mut cnn, pattern).await?;
+    if !keys.is_empty() {
+        let _: () = cnn.del(keys).await?;
+    }
+    Ok(())
+}
+
+#[rocket::async_trait]
+impl<Id: UserId> SessionManager<Id> for RedisPool {
+
+    async fn insert_for(&self, id: Id, session_id: &str, key: String, time: Duration) -> Result<()> {
+        let session_key = self.session_key(&id, session_id);
+        let secs = time.as_secs() as usize;
+        let _: () = self.query(|mut cnn| async move { cnn.set_ex(session_key, key, secs).await }).await?;
+        Ok(())
+    }
+
+    async fn refresh(&self, id: Id, session_id: &str, time: Duration) -> Result<()> {
+        let session_key = self.session_key(&id, session_id);
+        let secs = time.as_secs() as usize;
+        // EXPIRE leaves missing keys alone, so a session that was removed meanwhile stays removed.
+        let _: () = self.query(|mut cnn| async move { cnn.expire(session_key, secs).await }).await?;
         Ok(())
     }
 
-    fn insert_for(&self, id: ObjectId, key: String, time: Duration) -> Result<()> {
-        let mut cnn = self.get_connection()?;
-        cnn.set_ex(&id.bytes(), key, time.as_secs() as usize)?;
+    async fn remove(&self, id: Id, session_id: &str) -> Result<()> {
+        let session_key = self.session_key(&id, session_id);
+        let _: () = self.query(|mut cnn| async move { cnn.del(session_key).await }).await?;
         Ok(())
     }
 
-    fn remove(&self, id: ObjectId) -> Result<()> {
-        let mut cnn = self.get_connection()?;
-        cnn.del(&id.bytes())?;
+    async fn remove_all(&self, id: Id) -> Result<()> {
+        let pattern = prefix_pattern(&self.session_key(&id, ""));
+        self.query(|cnn| delete_matching(cnn, pattern)).await?;
         Ok(())
     }
 
-    fn get(&self, id: ObjectId) -> Option<String> {
-        let mut cnn = self.get_connection().ok()?;
-        let key = cnn.get(&id.bytes()).ok()?;
-        Some(key)
+    async fn get(&self, id: Id, session_id: &str) -> Option<String> {
+        let session_key = self.session_key(&id, session_id);
+        self.query(|mut cnn| async move { cnn.get(session_key).await }).await.ok()
+    }
+
+    async fn list(&self, id: Id) -> Result<Vec<SessionInfo>> {
+        let prefix = self.session_key(&id, "");
+        let sessions = self.query(|cnn| list_sessions(cnn, prefix)).await?;
+        Ok(sessions)
     }
 
-    fn clear_all(&self) -> Result<()> {
-        let mut cnn = self.get_connection()?;
-        redis::Cmd::new().arg("FLUSHDB").execute(&mut cnn);
+    async fn clear_all(&self) -> Result<()> {
+        let pattern = prefix_pattern(&self.options.key_prefix);
+        self.query(|cnn| delete_matching(cnn, pattern)).await?;
         Ok(())
     }
 
-    fn clear_expired(&self) -> Result<()> { Ok(())}
+    async fn clear_expired(&self) -> Result<()> { Ok(())}
+
+    /// Session keys are written with a TTL, so redis deletes them once they expire.
+    fn expires_sessions(&self) -> bool {
+        true
+    }
 }
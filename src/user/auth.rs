@@ -1,6 +1,6 @@
 use crate::prelude::*;
 use rocket::http::Status;
-use rocket::http::{Cookie, CookieJar};
+use rocket::http::CookieJar;
 use rocket::request::FromRequest;
 use rocket::request::Outcome;
 use rocket::Request;
@@ -30,12 +30,12 @@ use std::time::Duration;
 /// }
 ///
 /// #[get("/logout")]
-/// fn logout(auth: Auth) {
-///     auth.logout();
+/// async fn logout(auth: Auth<'_>) {
+///     auth.logout().await;
 /// }
 /// #[tokio::main]
 /// async fn main() -> Result<(), Error>{
-///     let users = Users::open_mongodb("mongodb://localhost:27017", DATABASE).await?;
+///     let users = Users::open_mongodb("mongodb://localhost:27017", "rocket_auth_nosql").await?;
 ///
 ///     rocket::build()
 ///         .mount("/", routes![signup, login, logout])
@@ -46,29 +46,41 @@ use std::time::Duration;
 /// }
 /// ```
 #[allow(missing_docs)]
-pub struct Auth<'a> {
+pub struct Auth<'a, Id: UserId = DefaultUserId> {
     /// `Auth` includes in its fields a [`Users`] instance. Therefore, it is not necessary to retrieve `Users` when using this guard.
-    pub users: &'a State<Users>,
+    pub users: &'a State<Users<Id>>,
     pub cookies: &'a CookieJar<'a>,
-    pub session: Option<Session>,
+    /// The session of the client, if it is authenticated.
+    pub session: Option<Session<Id>>,
 }
 
 #[async_trait]
-impl<'r> FromRequest<'r> for Auth<'r> {
+impl<'r, Id: UserId> FromRequest<'r> for Auth<'r, Id> {
     type Error = Error;
-    async fn from_request(req: &'r Request<'_>) -> Outcome<Auth<'r>, Error> {
-        let session: Option<Session> = if let Outcome::Success(users) = req.guard().await {
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Auth<'r, Id>, Error> {
+        let session: Option<Session<Id>> = if let Outcome::Success(users) = req.guard().await {
             Some(users)
         } else {
             None
         };
 
-        let users: &State<Users> = if let Outcome::Success(users) = req.guard().await {
+        let users: &State<Users<Id>> = if let Outcome::Success(users) = req.guard().await {
             users
         } else {
             return Outcome::Failure((Status::InternalServerError, Error::UnmanagedStateError));
         };
 
+        // The session store is only queried once per request, the other methods rely on this check.
+        let session = match session {
+            Some(session) => users.authenticate(session).await,
+            None => None,
+        };
+        if let Some(session) = &session {
+            if users.policy.idle_timeout.is_some() {
+                add_session_cookie(users, req.cookies(), session);
+            }
+        }
+
         Outcome::Success(Auth {
             users,
             session,
@@ -77,9 +89,9 @@ impl<'r> FromRequest<'r> for Auth<'r> {
     }
 }
 
-impl<'a> Auth<'a> {
+impl<'a, Id: UserId> Auth<'a, Id> {
     /// Logs in the user through a parsed form or json.
-    /// The session is set to expire in one year by default.
+    /// The session lasts for the maximum lifetime of the [`SessionPolicy`], which is one year by default.
     /// For a custom expiration date use [`Auth::login_for`].
     /// ```rust
     /// # use rocket::{get, post, form::Form};
@@ -89,18 +101,9 @@ impl<'a> Auth<'a> {
     ///     auth.login(&form);
     /// }
     /// ```
-
     pub async fn login(&self, form: &Login) -> Result<()> {
-        let key = self.users.login(form).await?;
-        let user = self.users.get_by_email(&form.email).await?;
-        let session = Session {
-            id: user.id.unwrap(),
-            email: user.email,
-            auth_key: key,
-            time_stamp: now(),
-        };
-        let to_str = format!("{}", json!(session));
-        self.cookies.add_private(Cookie::new("rocket_auth_nosql", to_str));
+        let session = self.users.login(form).await?;
+        add_session_cookie(self.users, self.cookies, &session);
         Ok(())
     }
 
@@ -115,25 +118,16 @@ impl<'a> Auth<'a> {
     ///     auth.login_for(&form, one_hour);
     /// }
     /// ```
-
     pub async fn login_for(&self, form: &Login, time: Duration) -> Result<()>  {
-        let key = self.users.login_for(form, time).await?;
-        let user = self.users.get_by_email(&form.email).await?;
-
-        let session = Session {
-            id: user.id.unwrap(),
-            email: user.email,
-            auth_key: key,
-            time_stamp: now(),
-        };
-        let to_str = format!("{}", json!(session));
-        let cookie = Cookie::new("rocket_auth_nosql", to_str);
-        self.cookies.add_private(cookie);
+        let session = self.users.login_for(form, time).await?;
+        add_session_cookie(self.users, self.cookies, &session);
         Ok(())
     }
 
     /// Creates a new user from a form or a json. The user will not be authenticated by default.
     /// In order to authenticate the user, cast the signup form to a login form or use `signup_for`.
+    /// If [`Users`] has a mailer, a verification email is sent to the new user, and the account
+    /// is not created when it cannot be sent. Otherwise the account is verified right away.
     /// ```rust
     /// # use rocket::{post, form::Form};
     /// # use rocket_auth_nosql::{Auth, Signup, Error};
@@ -145,10 +139,8 @@ impl<'a> Auth<'a> {
     ///     Ok("Logged in")
     /// }
     /// ```
-
     pub async fn signup(&self, form: &Signup) -> Result<()>  {
-        self.users.signup(form).await;
-        Ok(())
+        self.users.signup(form).await
     }
 
     /// Creates a new user from a form or a json.
@@ -163,9 +155,8 @@ impl<'a> Auth<'a> {
     ///     auth.signup_for(&form, one_hour);
     /// }
     /// ```
-
     pub async fn signup_for(&self, form: &Signup, time: Duration) -> Result<()>  {
-        self.users.signup(form).await;
+        self.users.signup(form).await?;
         self.login_for(&form.clone().into(), time).await?;
         Ok(())
     }
@@ -173,12 +164,13 @@ impl<'a> Auth<'a> {
     ///
     ///
     /// It allows to know if the current client is authenticated or not.
+    /// The session is checked when the guard is retrieved, so this does not query the session store.
     /// ```rust
     /// # use rocket::{get};
     /// # use rocket_auth_nosql::{Auth};
     /// #[get("/am-I-authenticated")]
-    /// fn is_auth(auth: Auth<'_>) -> &'static str {
-    ///     if auth.is_auth() {
+    /// async fn is_auth(auth: Auth<'_>) -> &'static str {
+    ///     if auth.is_auth().await {
     ///         "Yes you are."
     ///     } else {
     ///         "nope."
@@ -186,12 +178,8 @@ impl<'a> Auth<'a> {
     /// }
     /// # fn main() {}
     /// ```
-    pub fn is_auth(&self) -> bool {
-        if let Some(session) = &self.session {
-            self.users.is_auth(session)
-        } else {
-            false
-        }
+    pub async fn is_auth(&self) -> bool {
+        self.session.is_some()
     }
 
     /// It retrieves the current logged user.  
@@ -203,33 +191,71 @@ impl<'a> Auth<'a> {
     ///     format!("{:?}", auth.get_user().await)
     /// }
     /// ```
-    pub async fn get_user(&self) -> Option<User> {
-        if !self.is_auth() {
+    pub async fn get_user(&self) -> Option<User<Id>> {
+        if !self.is_auth().await {
             return None;
         }
-        let id = self.session.as_ref()?.id;
-        if let Ok(user) = self.users.get_by_id(id).await {
-            Some(user)
-        } else {
-            None
-        }
+        let id = self.session.as_ref()?.id.clone();
+        self.users.get_by_id(id).await.ok()
     }
-    /// Logs the currently authenticated user out.
+    /// Logs the current session out. Other sessions of the same user remain logged in.
     /// ```rust
     /// # use rocket::get;
     /// # use rocket_auth_nosql::Auth;
     /// #[get("/logout")]
-    /// fn logout(auth: Auth)  {
-    ///     auth.logout();
+    /// async fn logout(auth: Auth<'_>)  {
+    ///     auth.logout().await;
     /// }
     /// ```
-
-    pub fn logout(&self) -> Result<()>  {
+    pub async fn logout(&self) -> Result<()>  {
         let session = self.get_session()?;
-        self.users.logout(session);
-        self.cookies.remove_private(Cookie::named("rocket_auth_nosql"));
+        self.users.logout(session).await?;
+        self.cookies.remove_private(self.users.cookie.removal());
         Ok(())
     }
+    /// Logs out every session of the currently authenticated user, including the current one.
+    /// ```rust
+    /// # use rocket::get;
+    /// # use rocket_auth_nosql::Auth;
+    /// #[get("/logout-everywhere")]
+    /// async fn logout_everywhere(auth: Auth<'_>)  {
+    ///     auth.logout_everywhere().await;
+    /// }
+    /// ```
+    pub async fn logout_everywhere(&self) -> Result<()>  {
+        if self.is_auth().await {
+            let session = self.get_session()?;
+            self.users.sess.remove_all(session.id.clone()).await?;
+            self.cookies.remove_private(self.users.cookie.removal());
+            Ok(())
+        } else {
+            Err(Error::UnauthenticatedError)
+        }
+    }
+
+    /// Logs out every session of the currently authenticated user except the current one.
+    /// ```rust
+    /// # use rocket::post;
+    /// # use rocket_auth_nosql::{Auth, Error};
+    /// #[post("/sign-out-other-devices")]
+    /// async fn sign_out_other_devices(auth: Auth<'_>) -> Result<(), Error> {
+    ///     auth.logout_other_sessions().await
+    /// }
+    /// ```
+    pub async fn logout_other_sessions(&self) -> Result<()>  {
+        if self.is_auth().await {
+            let session = self.get_session()?;
+            for other in self.users.list_sessions(session.id.clone()).await? {
+                if other.session_id != session.session_id {
+                    self.users.revoke_session(session.id.clone(), &other.session_id).await?;
+                }
+            }
+            Ok(())
+        } else {
+            Err(Error::UnauthenticatedError)
+        }
+    }
+
     /// Deletes the account of the currently authenticated user.
     /// ```rust
     /// # use rocket::get;
@@ -239,12 +265,11 @@ impl<'a> Auth<'a> {
     ///     auth.delete();
     /// }
     /// ```
-
     pub async fn delete(&self)-> Result<()>  {
-        if self.is_auth() {
+        if self.is_auth().await {
             let session = self.get_session()?;
-            self.users.delete(session.id).await;
-            self.cookies.remove_private(Cookie::named("rocket_auth_nosql"));
+            self.users.delete(session.id.clone()).await?;
+            self.cookies.remove_private(self.users.cookie.removal());
             Ok(())
         } else {
             Err(Error::UnauthenticatedError)
@@ -260,13 +285,12 @@ impl<'a> Auth<'a> {
     ///     auth.change_password("new password");
     /// # }
     /// ```
-
     pub async fn change_password(&self, password: &str) -> Result<()>  {
-        if self.is_auth() {
+        if self.is_auth().await {
             let session = self.get_session()?;
-            let mut user = self.users.get_by_id(session.id).await?;
-            user.set_password(password)?;
-            self.users.modify(&user).await;
+            let mut user = self.users.get_by_id(session.id.clone()).await?;
+            user.set_password(password, &self.users.password_policy)?;
+            self.users.modify(&user).await?;
             Ok(())
         } else {
             Err(Error::UnauthorizedError)
@@ -281,13 +305,15 @@ impl<'a> Auth<'a> {
     ///     auth.change_password("new password");
     /// # }
     /// ```
-
     pub async fn verify_account(&self, token: &str) -> Result<()>  {
-        if self.is_auth() {
+        if self.is_auth().await {
             let session = self.get_session()?;
-            let mut user = self.users.get_by_id(session.id).await?;
+            let mut user = self.users.get_by_id(session.id.clone()).await?;
+            if self.users.verification.is_expired(user.verification_issued_at) {
+                return Err(Error::VerificationTokenExpired);
+            }
             user.set_verified(token)?;
-            self.users.modify(&user).await;
+            self.users.modify(&user).await?;
             Ok(())
         } else {
             Err(Error::VerificationTokenMismatch)
@@ -300,17 +326,15 @@ impl<'a> Auth<'a> {
     /// auth.change_email("new@email.com".into());
     /// # }
     /// ```
-
     pub async fn change_email(&self, email: String) -> Result<()>  {
-        if self.is_auth() {
+        if self.is_auth().await {
             if !validator::validate_email(&email) {
                 return Err(Error::InvalidEmailAddressError);
             }
             let session = self.get_session()?;
-            let mut user = self.users.get_by_id(session.id).await?;
+            let mut user = self.users.get_by_id(session.id.clone()).await?;
             user.email = email;
-            self.users.modify(&user).await;
-            Ok(())
+            self.users.modify(&user).await
         } else {
             Err(Error::UnauthorizedError)
         }
@@ -325,8 +349,14 @@ impl<'a> Auth<'a> {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn get_session(&self) -> Result<&Session> {
+    pub fn get_session(&self) -> Result<&Session<Id>> {
         let session = self.session.as_ref().ok_or(Error::UnauthenticatedError)?;
         Ok(session)
     }
 }
+
+fn add_session_cookie<Id: UserId>(users: &Users<Id>, cookies: &CookieJar<'_>, session: &Session<Id>) {
+    let to_str = format!("{}", json!(session));
+    let remaining = Duration::from_secs((session.expires - now()).max(0) as u64);
+    cookies.add_private(users.cookie.build(to_str, users.policy.ttl(remaining)));
+}
@@ -1,23 +1,46 @@
 pub mod auth;
+#[allow(clippy::module_inception)]
 mod user;
 mod users;
+pub(crate) mod verification;
 use crate::prelude::*;
 use argon2::verify_encoded as verify;
-use mongodb::bson::{oid::ObjectId};
+use sha2::{Digest, Sha256};
 
-use rand::random;
+use rand::distributions::Alphanumeric;
+use rand::rngs::OsRng;
+use rand::{random, thread_rng, Rng, RngCore};
 pub fn rand_string(size: usize) -> String {
     (0..)
         .map(|_| random::<char>())
         .filter(|c| c.is_ascii())
-        .map(char::from)
         .take(size)
         .collect()
 }
 
-impl Users {
-    fn is_auth(&self, session: &Session) -> bool {
-        let option = self.sess.get(session.id);
+/// Generates a new token to send by email, from 32 random bytes of the operating system.
+pub fn random_token() -> String {
+    let mut bytes = [0; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+/// Hashes a token sent by email. Only the hash is stored, so the tokens can't be read from the database.
+pub(crate) fn hash_token(token: &str) -> String {
+    format!("{:X}", Sha256::digest(token.as_bytes()))
+}
+
+pub fn rand_session_id() -> String {
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+impl<Id: UserId> Users<Id> {
+    async fn is_auth(&self, session: &Session<Id>) -> bool {
+        let option = self.sess.get(session.id.clone(), &session.session_id).await;
         if let Some(auth_key) = option {
             auth_key == session.auth_key
         } else {
@@ -25,7 +48,7 @@ impl Users {
         }
     }
 
-    async fn login(&self, form: &Login) -> Result<String> {
+    async fn login(&self, form: &Login) -> Result<Session<Id>> {
         let form_pwd = &form.password.as_bytes();
         let user = self
             .conn
@@ -34,59 +57,96 @@ impl Users {
             .map_err(|_| Error::EmailDoesNotExist(form.email.clone()))?;
         let user_pwd = &user.password;
         if verify(user_pwd, form_pwd)? {
-            self.set_auth_key(user.id.unwrap())
+            self.set_auth_key(user).await
         } else {
             Err(Error::UnauthorizedError)
         }
     }
 
-    fn logout(&self, session: &Session)-> Result<()>  {
-        if self.is_auth(session) {
-            self.sess.remove(session.id)?;
+    async fn logout(&self, session: &Session<Id>)-> Result<()>  {
+        if self.is_auth(session).await {
+            self.sess.remove(session.id.clone(), &session.session_id).await?;
         }
         Ok(())
     }
 
-    fn set_auth_key_for(&self, user_id: ObjectId, time: Duration) -> Result<String> {
-        let key = rand_string(10);
-        self.sess.insert_for(user_id, key.clone(), time)?;
-        Ok(key)
+    /// Checks a session against the session store, and extends its idle timeout without exceeding its lifetime.
+    /// It returns the session if it is still valid.
+    async fn authenticate(&self, session: Session<Id>) -> Option<Session<Id>> {
+        let remaining = session.expires - now();
+        if remaining <= 0 || !self.is_auth(&session).await {
+            return None;
+        }
+        if self.policy.idle_timeout.is_some() {
+            let time = self.policy.ttl(Duration::from_secs(remaining as u64));
+            if let Err(error) = self.sess.refresh(session.id.clone(), &session.session_id, time).await {
+                // The session is still valid, it will simply expire sooner.
+                log::warn!("Failed to refresh a session: {}", error);
+            }
+        }
+        Some(session)
     }
 
-    fn set_auth_key(&self, user_id: ObjectId) -> Result<String> {
+    async fn set_auth_key_for(&self, user: User<Id>, time: Duration) -> Result<Session<Id>> {
+        let session_id = rand_session_id();
         let key = rand_string(15);
-        self.sess.insert(user_id, key.clone())?;
-        Ok(key)
+        self.sess.insert_for(user.id(), &session_id, key.clone(), self.policy.ttl(time)).await?;
+        Ok(new_session(user, session_id, key, time))
+    }
+
+    async fn set_auth_key(&self, user: User<Id>) -> Result<Session<Id>> {
+        self.set_auth_key_for(user, self.policy.max_lifetime).await
     }
 
-    async fn signup(&self, form: &Signup) -> Result<()>  {
+    pub(crate) async fn signup(&self, form: &Signup) -> Result<()>  {
         form.validate()?;
+        crate::forms::is_secure(&form.password, &form.email, &self.password_policy)?;
         let email = &form.email;
         let password = &form.password;
-        let result = self.create_user(email, password, false).await;
-        match result {
-            Ok(_) => {
-                // Send an account verification e-mail if the Mailer is available, otherwise auto-activate
-                if self.mailer.is_some() {
-                    
+        let token = self.insert_user(email, password, false).await?;
+        let mut user = self.conn.get_user_by_email(email).await?;
+        if form.language.is_some() {
+            user.language = form.language;
+            self.conn.update_user(&user).await?;
+        }
+        // Send an account verification e-mail if the Mailer is available, otherwise auto-activate
+        match &self.mailer {
+            Some(mailer) => {
+                let sent = mailer.send_activation_email(&user.email, &token, user.language).await;
+                if let Err(error) = sent {
+                    // The user could never verify their account, so it's removed to let them sign up again.
+                    self.conn.delete_user_by_id(user.id()).await?;
+                    return Err(error);
                 }
-                Ok(())
-            },
-            Err(error) => {
-                Err(error)
+            }
+            None => {
+                user.is_verified = true;
+                self.conn.update_user(&user).await?;
             }
         }
-
+        Ok(())
     }
 
-    async fn login_for(&self, form: &Login, time: Duration) -> Result<String> {
+    async fn login_for(&self, form: &Login, time: Duration) -> Result<Session<Id>> {
         let form_pwd = &form.password.as_bytes();
         let user = self.conn.get_user_by_email(&form.email).await?;
         let user_pwd = &user.password;
         if verify(user_pwd, form_pwd)? {
-            Ok(self.set_auth_key_for(user.id.unwrap(), time)?)
+            self.set_auth_key_for(user, time).await
         } else {
             Err(Error::UnauthorizedError)
         }
     }
 }
+
+fn new_session<Id: UserId>(user: User<Id>, session_id: String, auth_key: String, time: Duration) -> Session<Id> {
+    let time_stamp = now();
+    Session {
+        id: user.id(),
+        email: user.email,
+        session_id,
+        auth_key,
+        time_stamp,
+        expires: time_stamp + time.as_secs() as i64,
+    }
+}
@@ -1,79 +1,70 @@
 use super::auth::Auth;
-use super::rand_string;
+use super::{hash_token, rand_string};
 
 use crate::prelude::*;
 use rocket::http::Status;
 use rocket::request::{FromRequest, Outcome, Request};
-use mongodb::bson::{oid::ObjectId};
 
-impl User {
+impl<Id: UserId> User<Id> {
     /// This method allows to reset the password of a user.
     /// In order for the new password to be saved, it must be passed to a [`Users`] instance.
     /// This function is meant for cases where the user lost their password.
     /// In case the user is authenticated,
     /// you can change it more easily with [`change_password`](`super::auth::Auth::change_password`).
-    /// This function will fail in case the password is not secure enough.
+    /// This function will fail in case the password doesn't follow the [`PasswordPolicy`],
+    /// or if it was used recently according to [`PasswordPolicy::history`].
+    /// The previous password is kept in the history of the user.
     /// ```rust
     /// # use rocket::{State, get};
     /// # use rocket_auth_nosql::{Error, Users};
     /// #[get("/reset-password/<id>/<new_password>")]
-    /// async fn reset_password(id: i32, new_password: String, users: &State<Users>) -> Result<(), Error> {
+    /// async fn reset_password(id: i64, new_password: String, users: &State<Users<i64>>) -> Result<(), Error> {
     ///     let mut user = users.get_by_id(id).await?;
-    ///     user.set_password(&new_password);
+    ///     user.set_password(&new_password, users.password_policy())?;
     ///     users.modify(&user).await?;
     ///     Ok(())
     /// }
     /// ```
-
-    pub fn set_password(&mut self, new: &str) -> Result<()> {
-        crate::forms::is_secure(new)?;
+    pub fn set_password(&mut self, new: &str, policy: &PasswordPolicy) -> Result<()> {
+        crate::forms::is_secure(new, &self.email, policy)?;
+        let recent = std::iter::once(&self.password).chain(&self.password_history);
+        for hash in recent.take(policy.history) {
+            if argon2::verify_encoded(hash, new.as_bytes())? {
+                return Err(Error::PasswordReusedError);
+            }
+        }
         let password = new.as_bytes();
         let salt = rand_string(10);
         let config = argon2::Config::default();
         let hash = argon2::hash_encoded(password, salt.as_bytes(), &config).unwrap();
-        self.password = hash;
+        let previous = std::mem::replace(&mut self.password, hash);
+        self.password_history.insert(0, previous);
+        self.password_history.truncate(policy.history.saturating_sub(1));
         Ok(())
     }
     /// This method sets the account flag to indicate the email address is verified.
+    /// The token is cleared, so it cannot be used again. It does not check whether the token has expired,
+    /// [`Users::verify_by_token`] does.
     /// ```rust
     /// # use rocket::{State, get};
     /// # use rocket_auth_nosql::{Error, Users};
-    /// #[get("/reset-password/<id>/<new_password>")]
-    /// async fn reset_password(id: i32, new_password: String, users: &State<Users>) -> Result<(), Error> {
+    /// #[get("/verify/<id>/<token>")]
+    /// async fn verify(id: i64, token: String, users: &State<Users<i64>>) -> Result<(), Error> {
     ///     let mut user = users.get_by_id(id).await?;
-    ///     user.set_password(&new_password);
+    ///     user.set_verified(&token)?;
     ///     users.modify(&user).await?;
     ///     Ok(())
     /// }
     /// ```
-
     pub fn set_verified(&mut self, token: &str) -> Result<()> {
-        if self.verification_token.eq(token) {
+        if !token.is_empty() && self.verification_token == hash_token(token) {
             self.is_verified = true;
+            self.verification_token.clear();
         } else {
             return Err(Error::VerificationTokenMismatch);
         }
         Ok(())
     }
-    /// Activates the account of a user using the token sent via email
-    /// ```
-    /// # use rocket_auth_nosql::Auth;
-    /// # use rocket::post;
-    /// # #[post("/activate")]
-    /// # fn example(auth: Auth<'_>) {
-    ///     auth.change_password("new password");
-    /// # }
-    /// ```
-    // pub fn activate_account(&self, token: &str) {
-    //     if self.is_auth() {
-    //         let session = self.get_session()?;
-    //         let mut user = self.users.get_by_id(session.id).await?;
-    //         //user.set_password(password)?;
-    //         //self.users.modify(&user).await?;
-    //     } else {
-    //         Err(Error::UnauthorizedError)
-    //     }
-    // }
 
     /// This is an accessor function for the private `id` field.
     /// This field is private so it is not modified by accident when updating a user.
@@ -85,8 +76,8 @@ impl User {
     ///     format!("Your user_id is: {}", user.id())
     /// }
     /// ```
-    pub fn id(&self) -> ObjectId {
-        self.id.unwrap()
+    pub fn id(&self) -> Id {
+        self.id.clone().unwrap()
     }
     /// This is an accessor field for the private `email` field.
     /// This field is private so an email cannot be updated without checking whether it is valid.
@@ -102,6 +93,27 @@ impl User {
         &self.email
     }
 
+    /// The language of the emails sent to the user, if it was chosen.
+    /// Otherwise they are written in [`MailerConfig::language`](crate::MailerConfig::language).
+    pub fn language(&self) -> Option<Language> {
+        self.language
+    }
+
+    /// Changes the language of the emails sent to the user, `None` meaning the language of the mailer.
+    /// ```rust
+    /// # use rocket::{State, post};
+    /// # use rocket_auth_nosql::{Error, Auth, Language};
+    /// #[post("/language?<language>")]
+    /// async fn set_language(language: Language, auth: Auth<'_>) -> Result<(), Error> {
+    ///     let mut user = auth.get_user().await.ok_or(Error::UnauthenticatedError)?;
+    ///     user.set_language(Some(language));
+    ///     auth.users.modify(&user).await
+    /// }
+    /// ```
+    pub fn set_language(&mut self, language: Option<Language>) {
+        self.language = language;
+    }
+
     /// This functions allows to easily modify the email of a user.
     /// In case the input is not a valid email, it will return an error.
     /// In case the user corresponds to the authenticated client, it's easier to use [`Auth::change_email`].
@@ -116,10 +128,10 @@ impl User {
     ///     Ok("Your user email was changed".into())
     /// }
     /// ```
-
     pub fn set_email(&mut self, email: &str) -> Result<()> {
         if validator::validate_email(email) {
-            Ok(self.email = email.into())
+            self.email = email.into();
+            Ok(())
         } else {
             Err(Error::InvalidEmailAddressError)
         }
@@ -128,7 +140,7 @@ impl User {
 
 use std::fmt::{self, Debug};
 
-impl Debug for User {
+impl<Id: Debug> Debug for User<Id> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
@@ -139,12 +151,12 @@ impl Debug for User {
 }
 
 #[rocket::async_trait]
-impl<'r> FromRequest<'r> for User {
+impl<'r, Id: UserId> FromRequest<'r> for User<Id> {
     type Error = Error;
-    async fn from_request(request: &'r Request<'_>) -> Outcome<User, Error> {
+    async fn from_request(request: &'r Request<'_>) -> Outcome<User<Id>, Error> {
         use rocket::outcome::Outcome::*;
         let guard = request.guard().await;
-        let auth: Auth = match guard {
+        let auth: Auth<Id> = match guard {
             Success(auth) => auth,
             Failure(x) => return Failure(x),
             Forward(x) => return Forward(x),
@@ -162,12 +174,12 @@ impl<'r> FromRequest<'r> for User {
 }
 
 #[rocket::async_trait]
-impl<'r> FromRequest<'r> for UnverifiedUser {
+impl<'r, Id: UserId> FromRequest<'r> for UnverifiedUser<Id> {
     type Error = Error;
-    async fn from_request(request: &'r Request<'_>) -> Outcome<UnverifiedUser, Error> {
+    async fn from_request(request: &'r Request<'_>) -> Outcome<UnverifiedUser<Id>, Error> {
         use rocket::outcome::Outcome::*;
         let guard = request.guard().await;
-        let auth: Auth = match guard {
+        let auth: Auth<Id> = match guard {
             Success(auth) => auth,
             Failure(x) => return Failure(x),
             Forward(x) => return Forward(x),
@@ -181,12 +193,12 @@ impl<'r> FromRequest<'r> for UnverifiedUser {
 }
 
 #[rocket::async_trait]
-impl<'r> FromRequest<'r> for AdminUser {
+impl<'r, Id: UserId> FromRequest<'r> for AdminUser<Id> {
     type Error = Error;
-    async fn from_request(request: &'r Request<'_>) -> Outcome<AdminUser, Error> {
+    async fn from_request(request: &'r Request<'_>) -> Outcome<AdminUser<Id>, Error> {
         use rocket::outcome::Outcome::*;
         let guard = request.guard().await;
-        let auth: Auth = match guard {
+        let auth: Auth<Id> = match guard {
             Success(auth) => auth,
             Failure(x) => return Failure(x),
             Forward(x) => return Forward(x),
@@ -201,20 +213,31 @@ impl<'r> FromRequest<'r> for AdminUser {
 }
 
 use std::ops::*;
-impl Deref for AdminUser {
-    type Target = User;
+impl<Id> Deref for UnverifiedUser<Id> {
+    type Target = User<Id>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<Id> DerefMut for UnverifiedUser<Id> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+impl<Id> Deref for AdminUser<Id> {
+    type Target = User<Id>;
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
-impl DerefMut for AdminUser {
+impl<Id> DerefMut for AdminUser<Id> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.0
     }
 }
-impl std::convert::TryFrom<User> for AdminUser {
+impl<Id> std::convert::TryFrom<User<Id>> for AdminUser<Id> {
     type Error = Error;
-    fn try_from(value: User) -> Result<Self> {
+    fn try_from(value: User<Id>) -> Result<Self> {
         if value.is_admin {
             Ok(AdminUser(value))
         } else {
@@ -0,0 +1,163 @@
+use crate::prelude::*;
+use rocket::http::{ContentType, Method};
+use rocket::response::Redirect;
+use rocket::route::{Handler, Outcome, Route};
+use rocket::{Data, Request};
+use std::marker::PhantomData;
+
+/// The lifetime of account verification and password reset tokens, set with [`Users::set_verification_policy`].
+/// ```rust
+/// # use rocket_auth_nosql::VerificationPolicy;
+/// # use std::time::Duration;
+/// // Verification links work for two hours, and can be sent again every five minutes.
+/// let policy = VerificationPolicy {
+///     token_ttl: Duration::from_secs(2 * 60 * 60),
+///     resend_interval: Duration::from_secs(5 * 60),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct VerificationPolicy {
+    /// How long a verification token can be used after it is issued. It defaults to one day.
+    #[serde(deserialize_with = "crate::config::deserialize_secs")]
+    pub token_ttl: Duration,
+    /// How long [`Users::resend_verification`] and [`Users::request_password_reset`] wait
+    /// before issuing another token. It defaults to one minute.
+    #[serde(deserialize_with = "crate::config::deserialize_secs")]
+    pub resend_interval: Duration,
+    /// How long a password reset token can be used after it is issued. It defaults to one hour.
+    #[serde(deserialize_with = "crate::config::deserialize_secs")]
+    pub reset_token_ttl: Duration,
+}
+
+impl Default for VerificationPolicy {
+    fn default() -> Self {
+        VerificationPolicy {
+            token_ttl: Duration::from_secs(24 * 60 * 60),
+            resend_interval: Duration::from_secs(60),
+            reset_token_ttl: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl VerificationPolicy {
+    /// Whether a token issued at the Unix time `issued_at` can no longer be used.
+    pub(crate) fn is_expired(&self, issued_at: i64) -> bool {
+        now() >= issued_at.saturating_add(self.token_ttl.as_secs() as i64)
+    }
+
+    /// Whether a password reset token issued at the Unix time `issued_at` can no longer be used.
+    pub(crate) fn is_reset_expired(&self, issued_at: i64) -> bool {
+        now() >= issued_at.saturating_add(self.reset_token_ttl.as_secs() as i64)
+    }
+
+    /// Whether a token issued at the Unix time `issued_at` is too recent to be replaced.
+    pub(crate) fn is_throttled(&self, issued_at: i64) -> bool {
+        now() < issued_at.saturating_add(self.resend_interval.as_secs() as i64)
+    }
+}
+
+/// The page shown by the `GET` route of [`VerifyAccount`], whose form posts the token to the same url.
+const CONFIRMATION_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>Verify your account</title></head>
+<body>
+<form method="post">
+<p>Confirm the verification of your account.</p>
+<button type="submit">Verify my account</button>
+</form>
+</body>
+</html>
+"#;
+
+/// The routes that verify accounts with the token sent by email, see [`Users::verify_by_token`].
+/// They are mounted at `<base>/<token>`, so verification links work in any browser, logged in or not.
+/// Opening the link shows a page with a button that submits the token with a `POST` request to the same url,
+/// so link previews and email scanners, which only fetch the page, don't use the token up.
+/// On success the `POST` route answers with a JSON status, or redirects to the page given to
+/// [`VerifyAccount::redirect_to`]. Failures are answered with the [`Error`].
+/// ```rust,no_run
+/// # use rocket_auth_nosql::{DefaultUserId, Error, MailerConfig, Users, VerifyAccount};
+/// # async fn func() -> Result<(), Error> {
+/// let config = MailerConfig {
+///     from: "accounts@example.com".into(),
+///     verification_url: Some("https://example.com/verify/".into()),
+///     ..Default::default()
+/// };
+/// let users = Users::open_mongodb("mongodb://localhost:27017", "rocket_auth_nosql").await?
+///     .with_mailer(config)?;
+///
+/// rocket::build()
+///     .mount("/verify", VerifyAccount::<DefaultUserId>::new().redirect_to("/welcome"))
+///     .manage(users)
+///     .launch();
+/// # Ok(()) }
+/// ```
+pub struct VerifyAccount<Id = DefaultUserId> {
+    redirect: Option<String>,
+    _id: PhantomData<fn() -> Id>,
+}
+
+impl<Id: UserId> VerifyAccount<Id> {
+    pub fn new() -> Self {
+        VerifyAccount {
+            redirect: None,
+            _id: PhantomData,
+        }
+    }
+
+    /// Redirects clients to `uri` once their account is verified.
+    pub fn redirect_to(mut self, uri: impl Into<String>) -> Self {
+        self.redirect = Some(uri.into());
+        self
+    }
+}
+
+impl<Id: UserId> Default for VerifyAccount<Id> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Id> Clone for VerifyAccount<Id> {
+    fn clone(&self) -> Self {
+        VerifyAccount {
+            redirect: self.redirect.clone(),
+            _id: PhantomData,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<Id: UserId> Handler for VerifyAccount<Id> {
+    async fn handle<'r>(&self, req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r> {
+        let token = match req.param::<&str>(0) {
+            Some(Ok(token)) => token,
+            _ => return Outcome::forward(data),
+        };
+        if req.method() == Method::Get {
+            return Outcome::from(req, (ContentType::HTML, CONFIRMATION_PAGE));
+        }
+        let users = match req.rocket().state::<Users<Id>>() {
+            Some(users) => users,
+            None => return Outcome::from(req, Error::UnmanagedStateError),
+        };
+        if let Err(error) = users.verify_by_token(token).await {
+            return Outcome::from(req, error);
+        }
+        match &self.redirect {
+            Some(uri) => Outcome::from(req, Redirect::to(uri.clone())),
+            None => Outcome::from(req, (ContentType::JSON, r#"{"status":"success"}"#)),
+        }
+    }
+}
+
+impl<Id: UserId> From<VerifyAccount<Id>> for Vec<Route> {
+    fn from(handler: VerifyAccount<Id>) -> Vec<Route> {
+        vec![
+            Route::new(Method::Get, "/<token>", handler.clone()),
+            Route::new(Method::Post, "/<token>", handler),
+        ]
+    }
+}